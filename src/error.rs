@@ -0,0 +1,82 @@
+use std::fmt::Display;
+
+/// Error returned when parsing a string that is not a declared variant.
+///
+/// Returned by `try_new` and by [`FromStr`](std::str::FromStr) on `#[strict]` types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownVariant {
+    input: String,
+    expected: &'static [&'static str],
+    suggestion: Option<&'static str>,
+}
+
+impl UnknownVariant {
+    #[doc(hidden)]
+    pub fn new(
+        input: &str,
+        expected: &'static [&'static str],
+        suggestion: Option<&'static str>,
+    ) -> Self {
+        Self {
+            input: input.to_owned(),
+            expected,
+            suggestion,
+        }
+    }
+
+    /// The rejected input, as provided.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// String values of all declared variants.
+    pub fn expected(&self) -> &'static [&'static str] {
+        self.expected
+    }
+
+    /// The declared variant closest to the input, if any is similar enough.
+    pub fn suggestion(&self) -> Option<&'static str> {
+        self.suggestion
+    }
+}
+
+impl Display for UnknownVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown variant \"{}\"", self.input)?;
+        if let Some(suggestion) = self.suggestion {
+            return write!(f, ", did you mean \"{}\"?", suggestion);
+        }
+        match self.expected {
+            [] => f.write_str(", there are no variants"),
+            [first, rest @ ..] => {
+                write!(f, ", expected one of \"{}\"", first)?;
+                for name in rest {
+                    write!(f, ", \"{}\"", name)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for UnknownVariant {}
+
+/// Error returned by [`Flags::parse_strict`](crate::Flags::parse_strict).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseFlagsError<E> {
+    /// The item at this position is empty or whitespace.
+    EmptyItem(usize),
+    /// An item failed to parse.
+    Item(E),
+}
+
+impl<E: Display> Display for ParseFlagsError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseFlagsError::EmptyItem(i) => write!(f, "item {} is empty", i),
+            ParseFlagsError::Item(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: std::error::Error> std::error::Error for ParseFlagsError<E> {}
@@ -0,0 +1,374 @@
+//! Boolean expressions over flags, e.g. `"dog & !cat | whale"`.
+//!
+//! * `a & b` matches if both `a` and `b` match.
+//! * `a | b` matches if either `a` or `b` matches.
+//! * `!a` matches if `a` does not match.
+//! * Parentheses group sub-expressions.
+//!
+//! `!` binds tightest, followed by `&`, then `|`.
+//! Items are trimmed and parsed with [`FromStr`], so they are normalized like `new`,
+//! and `#[strict]` types reject undeclared variants.
+//!
+//! Items cannot contain whitespace, so a missing operator like `"dog cat"` is an error.
+//!
+//! Chains of `&` and `|` are stored flat, and `!` and parentheses nest at most
+//! [`MAX_DEPTH`] levels deep, deeper input is rejected with [`ExprErrorKind::TooDeep`],
+//! so evaluating, formatting and dropping parsed expressions cannot overflow the stack.
+//!
+//! ```
+//! # use strflags::*;
+//! # use strflags::expr::Expr;
+//! # str_flags! { Animal: [ Dog, Cat, Whale ] }
+//! let expr: Expr<Animal> = "Dog & !Cat | whale".parse().unwrap();
+//! assert!(expr.eval(&(Animal::Dog | Animal::Whale)));
+//! assert!(!expr.eval(&(Animal::Dog | Animal::Cat)));
+//!
+//! let flags = Animal::Cat | Animal::Whale;
+//! assert!(flags.contains(&expr));
+//! ```
+use std::fmt::Display;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::query::Lookup;
+use crate::set::bit;
+use crate::{FlagsMarker, Query, StrEnum};
+
+/// Maximum nesting of `!` and parentheses accepted by [`Expr::parse`].
+pub const MAX_DEPTH: usize = 256;
+
+/// A parsed flag expression.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expr<T> {
+    /// Matches if the item is in the set.
+    Item(T),
+    /// `!a`
+    Not(Box<Expr<T>>),
+    /// `a & b & ...`, parsed with at least two operands.
+    And(Vec<Expr<T>>),
+    /// `a | b | ...`, parsed with at least two operands.
+    Or(Vec<Expr<T>>),
+}
+
+impl<T: StrEnum> Expr<T> {
+    /// Parse an expression, items are parsed with [`FromStr`].
+    pub fn parse(s: &str) -> Result<Self, ParseExprError<T::Err>> where T: FromStr {
+        let mut parser = Parser { tokens: tokenize(s), pos: 0, len: s.len(), depth: 0 };
+        let expr = parser.or()?;
+        match parser.next() {
+            None => Ok(expr),
+            Some((token, span)) => Err(token.unexpected(span)),
+        }
+    }
+
+    /// Returns `true` if `set` satisfies this expression.
+    pub fn eval<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        self.eval_with(&mut |item| match (bit(item), set.known_bits()) {
+            (Some(bit), Some(known)) => known & bit != 0,
+            _ => set.contains_str(item.as_ref()),
+        })
+    }
+
+    fn eval_with(&self, f: &mut impl FnMut(&T) -> bool) -> bool {
+        match self {
+            Expr::Item(item) => f(item),
+            Expr::Not(a) => !a.eval_with(f),
+            Expr::And(items) => items.iter().all(|x| x.eval_with(f)),
+            Expr::Or(items) => items.iter().any(|x| x.eval_with(f)),
+        }
+    }
+
+    /// Iterate over the items referenced by this expression, from left to right.
+    pub fn items(&self) -> impl Iterator<Item = &T> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || loop {
+            match stack.pop()? {
+                Expr::Item(item) => return Some(item),
+                Expr::Not(a) => stack.push(a),
+                Expr::And(items) | Expr::Or(items) => stack.extend(items.iter().rev()),
+            }
+        })
+    }
+
+    /// Binding strength, used to add parentheses in [`Display`].
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Or(..) => 0,
+            Expr::And(..) => 1,
+            Expr::Not(_) | Expr::Item(_) => 2,
+        }
+    }
+
+    fn fmt_chain(f: &mut std::fmt::Formatter<'_>, items: &[Self], op: &str, precedence: u8) -> std::fmt::Result
+            where T: Display {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                f.write_str(op)?;
+            }
+            item.fmt_operand(f, precedence)?;
+        }
+        Ok(())
+    }
+
+    fn fmt_operand(&self, f: &mut std::fmt::Formatter<'_>, precedence: u8) -> std::fmt::Result where T: Display {
+        if self.precedence() < precedence {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+/// Formats with the minimal parentheses, parsing the output returns an equal expression.
+impl<T: StrEnum + Display> Display for Expr<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Item(item) => item.fmt(f),
+            Expr::Not(a) => {
+                f.write_str("!")?;
+                a.fmt_operand(f, 2)
+            }
+            Expr::And(items) => Self::fmt_chain(f, items, " & ", 2),
+            Expr::Or(items) => Self::fmt_chain(f, items, " | ", 1),
+        }
+    }
+}
+
+impl<T: StrEnum + FromStr> FromStr for Expr<T> {
+    type Err = ParseExprError<T::Err>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Matches if the set satisfies the expression.
+impl<T: FlagsMarker> Query<T> for Expr<T> {
+    type Iter<'t> = std::vec::IntoIter<&'t str>;
+    fn items<'t>(&'t self) -> Self::Iter<'t> {
+        Expr::items(self).map(|x| x.as_ref()).collect::<Vec<_>>().into_iter()
+    }
+    fn matches<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        self.eval(set)
+    }
+    fn matches_all<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        self.eval(set)
+    }
+    fn is_match(&self, s: &str) -> bool {
+        self.eval_with(&mut |item| item.as_ref() == s)
+    }
+}
+
+impl<T: FlagsMarker> Query<T> for &Expr<T> {
+    type Iter<'t> = std::vec::IntoIter<&'t str> where Self: 't;
+    fn items<'t>(&'t self) -> Self::Iter<'t> {
+        Expr::items(*self).map(|x| x.as_ref()).collect::<Vec<_>>().into_iter()
+    }
+    fn matches<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        self.eval(set)
+    }
+    fn matches_all<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        self.eval(set)
+    }
+    fn is_match(&self, s: &str) -> bool {
+        self.eval_with(&mut |item| item.as_ref() == s)
+    }
+}
+
+/// Error returned when parsing an [`Expr`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseExprError<E> {
+    kind: ExprErrorKind<E>,
+    span: Range<usize>,
+}
+
+/// Reason an [`Expr`] failed to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExprErrorKind<E> {
+    /// The input ended where an item was expected.
+    UnexpectedEnd,
+    /// An operator or parenthesis appeared where it is not allowed.
+    Unexpected(char),
+    /// An item appeared where an operator was expected, e.g. `"(dog) cat"`.
+    UnexpectedItem,
+    /// Operators or parentheses are nested deeper than [`MAX_DEPTH`].
+    TooDeep,
+    /// A `(` without a matching `)`.
+    Unclosed,
+    /// An item failed to parse.
+    Item(E),
+}
+
+impl<E> ParseExprError<E> {
+    fn new(kind: ExprErrorKind<E>, span: Range<usize>) -> Self {
+        Self { kind, span }
+    }
+
+    pub fn kind(&self) -> &ExprErrorKind<E> {
+        &self.kind
+    }
+
+    /// Byte range of the input the error refers to.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl<E: Display> Display for ParseExprError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ExprErrorKind::UnexpectedEnd => f.write_str("unexpected end of expression")?,
+            ExprErrorKind::Unexpected(c) => write!(f, "unexpected `{}`", c)?,
+            ExprErrorKind::UnexpectedItem => f.write_str("unexpected item")?,
+            ExprErrorKind::TooDeep => write!(f, "expression nested deeper than {}", MAX_DEPTH)?,
+            ExprErrorKind::Unclosed => f.write_str("unclosed `(`")?,
+            ExprErrorKind::Item(e) => e.fmt(f)?,
+        }
+        write!(f, " at {}..{}", self.span.start, self.span.end)
+    }
+}
+
+impl<E: std::error::Error> std::error::Error for ParseExprError<E> {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'t> {
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+    Item(&'t str),
+}
+
+impl Token<'_> {
+    /// Error for this token appearing where it is not allowed.
+    fn unexpected<E>(self, span: Range<usize>) -> ParseExprError<E> {
+        let kind = match self {
+            Token::And => ExprErrorKind::Unexpected('&'),
+            Token::Or => ExprErrorKind::Unexpected('|'),
+            Token::Not => ExprErrorKind::Unexpected('!'),
+            Token::Open => ExprErrorKind::Unexpected('('),
+            Token::Close => ExprErrorKind::Unexpected(')'),
+            Token::Item(_) => ExprErrorKind::UnexpectedItem,
+        };
+        ParseExprError::new(kind, span)
+    }
+}
+
+/// Split `s` into operators and items, with their byte ranges.
+///
+/// Words separated by whitespace are separate items, so the parser rejects the second one.
+fn tokenize(s: &str) -> Vec<(Token<'_>, Range<usize>)> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let push_item = |tokens: &mut Vec<_>, start: usize, end: usize| {
+        let mut rest = &s[start..end];
+        loop {
+            let trimmed = rest.trim_start();
+            if trimmed.is_empty() {
+                break;
+            }
+            let offset = end - trimmed.len();
+            let len = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+            tokens.push((Token::Item(&trimmed[..len]), offset..offset + len));
+            rest = &trimmed[len..];
+        }
+    };
+    for (i, c) in s.char_indices() {
+        let token = match c {
+            '&' => Token::And,
+            '|' => Token::Or,
+            '!' => Token::Not,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            _ => continue,
+        };
+        push_item(&mut tokens, start, i);
+        tokens.push((token, i..i + 1));
+        start = i + 1;
+    }
+    push_item(&mut tokens, start, s.len());
+    tokens
+}
+
+type Parsed<T, E> = Result<Expr<T>, ParseExprError<E>>;
+
+struct Parser<'t> {
+    tokens: Vec<(Token<'t>, Range<usize>)>,
+    pos: usize,
+    len: usize,
+    /// Number of `!` and `(` currently being parsed.
+    depth: usize,
+}
+
+impl<'t> Parser<'t> {
+    fn peek(&self) -> Option<(Token<'t>, Range<usize>)> {
+        self.tokens.get(self.pos).cloned()
+    }
+
+    fn next(&mut self) -> Option<(Token<'t>, Range<usize>)> {
+        let result = self.peek();
+        self.pos += 1;
+        result
+    }
+
+    fn or<T: StrEnum + FromStr>(&mut self) -> Parsed<T, T::Err> {
+        let mut items = vec![self.and()?];
+        while let Some((Token::Or, _)) = self.peek() {
+            self.pos += 1;
+            items.push(self.and()?);
+        }
+        Ok(Self::chain(items, Expr::Or))
+    }
+
+    fn and<T: StrEnum + FromStr>(&mut self) -> Parsed<T, T::Err> {
+        let mut items = vec![self.unary()?];
+        while let Some((Token::And, _)) = self.peek() {
+            self.pos += 1;
+            items.push(self.unary()?);
+        }
+        Ok(Self::chain(items, Expr::And))
+    }
+
+    /// The single operand, or a chain of at least two.
+    fn chain<T>(mut items: Vec<Expr<T>>, f: impl FnOnce(Vec<Expr<T>>) -> Expr<T>) -> Expr<T> {
+        match items.len() {
+            1 => items.remove(0),
+            _ => f(items),
+        }
+    }
+
+    fn unary<T: StrEnum + FromStr>(&mut self) -> Parsed<T, T::Err> {
+        let Some((token, span)) = self.next() else {
+            return Err(ParseExprError::new(ExprErrorKind::UnexpectedEnd, self.len..self.len));
+        };
+        match token {
+            Token::Not | Token::Open => {
+                if self.depth == MAX_DEPTH {
+                    return Err(ParseExprError::new(ExprErrorKind::TooDeep, span));
+                }
+                self.depth += 1;
+                let result = self.nested(token, span);
+                self.depth -= 1;
+                result
+            }
+            Token::Item(item) => T::from_str(item)
+                .map(Expr::Item)
+                .map_err(|e| ParseExprError::new(ExprErrorKind::Item(e), span)),
+            token => Err(token.unexpected(span)),
+        }
+    }
+
+    /// Operand of a `!` or the inside of a `(`.
+    fn nested<T: StrEnum + FromStr>(&mut self, token: Token<'t>, span: Range<usize>) -> Parsed<T, T::Err> {
+        if token == Token::Not {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        let result = self.or()?;
+        match self.next() {
+            Some((Token::Close, _)) => Ok(result),
+            Some((token, span)) => Err(token.unexpected(span)),
+            None => Err(ParseExprError::new(ExprErrorKind::Unclosed, span)),
+        }
+    }
+}
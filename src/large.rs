@@ -0,0 +1,375 @@
+use std::borrow::Borrow;
+use std::collections::{hash_set, HashSet};
+use std::fmt::Display;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ops::*;
+use std::str::FromStr;
+
+use crate::separator::{split_escaped, write_separated};
+use crate::{Flags, FlagsMarker, Query, StrEnum};
+
+/// A set of string-enums backed by a [`HashSet`].
+///
+/// Supports the operators of [`Flags`] with a `LargeFlags` on the left-hand side,
+/// and the same set predicates, but each operation is `O(1)` per item instead of `O(n)`,
+/// this is faster for sets with many items.
+///
+/// Since there is no insertion order, [`Display`] and serialization
+/// always use the canonical order, see [`Flags::to_canonical_string`].
+///
+/// Variant hierarchies are not applied, a set holding only `Admin`
+/// does not contain the variants `Admin` implies, use [`Flags::expand`] before converting.
+#[derive(Clone)]
+pub struct LargeFlags<T, const SEP: char='|'>(pub(crate) HashSet<T>);
+
+impl<T: Eq + Hash, const S: char> LargeFlags<T, S> {
+    #[inline]
+    pub fn empty() -> Self {
+        Self(HashSet::new())
+    }
+
+    #[inline]
+    pub fn new(value: T) -> Self {
+        let mut set = HashSet::with_capacity(1);
+        set.insert(value);
+        Self(set)
+    }
+
+    #[inline]
+    pub fn pair(value1: T, value2: T) -> Self {
+        let mut set = HashSet::with_capacity(2);
+        set.insert(value1);
+        set.insert(value2);
+        Self(set)
+    }
+
+    /// Returns a set containing every declared variant.
+    #[inline]
+    pub fn all() -> Self where T: StrEnum {
+        Self(T::VARIANTS.iter().cloned().collect())
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn is_some(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn is_none(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns `true` if any item of `t` is in the set.
+    #[inline]
+    pub fn contains(&self, t: impl Query<T>) -> bool where T: FlagsMarker + Borrow<str> {
+        t.matches(self)
+    }
+
+    /// Returns `true` if every item of `t` is in the set.
+    #[inline]
+    pub fn contains_all(&self, t: impl Query<T>) -> bool where T: FlagsMarker + Borrow<str> {
+        t.matches_all(self)
+    }
+
+    /// Returns `true` if no item of `t` is in the set.
+    #[inline]
+    pub fn contains_none(&self, t: impl Query<T>) -> bool where T: FlagsMarker + Borrow<str> {
+        !t.matches(self)
+    }
+
+    /// Returns `true` if every item of the set is matched by `t`.
+    pub fn is_subset(&self, t: impl Query<T>) -> bool where T: FlagsMarker + Borrow<str> {
+        self.0.iter().all(|x| t.is_match(x.as_ref()))
+    }
+
+    /// Returns `true` if every item of `t` is in the set, same as [`LargeFlags::contains_all`].
+    #[inline]
+    pub fn is_superset(&self, t: impl Query<T>) -> bool where T: FlagsMarker + Borrow<str> {
+        t.matches_all(self)
+    }
+
+    /// Returns `true` if no item of `t` is in the set, same as [`LargeFlags::contains_none`].
+    #[inline]
+    pub fn is_disjoint(&self, t: impl Query<T>) -> bool where T: FlagsMarker + Borrow<str> {
+        !t.matches(self)
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.0.iter()
+    }
+
+    /// Iterate in canonical order,
+    /// declared variants in declaration order, then custom values sorted lexicographically.
+    pub fn iter_canonical(&self) -> impl Iterator<Item = &T> where T: StrEnum {
+        let mut items: Vec<&T> = self.0.iter().collect();
+        items.sort_by(|a, b| (a.known_index().is_none(), a.known_index(), a.as_ref())
+            .cmp(&(b.known_index().is_none(), b.known_index(), b.as_ref())));
+        items.into_iter()
+    }
+
+    /// Format in canonical order, same as [`Display`].
+    pub fn to_canonical_string(&self) -> String where T: StrEnum + Display {
+        self.to_string()
+    }
+}
+
+impl<T: Eq + Hash, const S: char> Default for LargeFlags<T, S> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T: Eq + Hash, const S: char> PartialEq for LargeFlags<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: Eq + Hash, const S: char> Eq for LargeFlags<T, S> {}
+
+/// Independent of iteration order, equal sets have equal hashes.
+impl<T: Eq + Hash, const S: char> Hash for LargeFlags<T, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut sum = 0u64;
+        for item in self.0.iter() {
+            let mut hasher = DefaultHasher::new();
+            item.hash(&mut hasher);
+            sum = sum.wrapping_add(hasher.finish());
+        }
+        state.write_usize(self.0.len());
+        state.write_u64(sum);
+    }
+}
+
+impl<T: Eq + Hash, const S: char> FromIterator<T> for LargeFlags<T, S> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<T: Eq + Hash, const S: char> Extend<T> for LargeFlags<T, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+impl<T: StrEnum + Eq + Hash, const S: char> From<Flags<T, S>> for LargeFlags<T, S> {
+    fn from(flags: Flags<T, S>) -> Self {
+        flags.into_iter().collect()
+    }
+}
+
+impl<T: StrEnum + Eq + Hash, const S: char> From<LargeFlags<T, S>> for Flags<T, S> {
+    fn from(flags: LargeFlags<T, S>) -> Self {
+        flags.0.into_iter().collect()
+    }
+}
+
+impl<T, const S: char> IntoIterator for LargeFlags<T, S> {
+    type Item = T;
+    type IntoIter = hash_set::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'t, T, const S: char> IntoIterator for &'t LargeFlags<T, S> {
+    type Item = &'t T;
+    type IntoIter = hash_set::Iter<'t, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<T: Eq + Hash, const S: char> std::fmt::Debug for LargeFlags<T, S> where T: StrEnum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set()
+            .entries(self.iter_canonical().map(|x| x.as_ref()))
+            .finish()
+    }
+}
+
+/// Items separated by `S`, in canonical order.
+impl<T: Eq + Hash, const S: char> Display for LargeFlags<T, S> where T: StrEnum + Display {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_separated(f, &S, self.iter_canonical())
+    }
+}
+
+/// Same format as [`Flags`].
+impl<T: Eq + Hash, const S: char> FromStr for LargeFlags<T, S> where T: StrEnum + FromStr {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        split_escaped(s, &S)
+            .filter(|item| !item.is_empty())
+            .map(|item| T::from_str(&item))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl<T: Eq + Hash, const S: char> Add<T> for LargeFlags<T, S> {
+    type Output = Self;
+    fn add(mut self, rhs: T) -> Self{
+        self.0.insert(rhs);
+        self
+    }
+}
+
+impl<T: Eq + Hash, const S: char> Sub<T> for LargeFlags<T, S> {
+    type Output = Self;
+    fn sub(mut self, rhs: T) -> Self{
+        self.0.remove(&rhs);
+        self
+    }
+}
+
+impl<T: Eq + Hash, const S: char> BitOr<T> for LargeFlags<T, S> {
+    type Output = Self;
+    fn bitor(mut self, rhs: T) -> Self{
+        self.0.insert(rhs);
+        self
+    }
+}
+
+impl<T: Eq + Hash, const S: char> BitAnd<T> for LargeFlags<T, S> {
+    type Output = Self;
+    fn bitand(self, rhs: T) -> Self{
+        if self.0.contains(&rhs) {
+            Self::new(rhs)
+        } else {
+            Self::empty()
+        }
+    }
+}
+
+impl<T: Eq + Hash, const S: char> BitXor<T> for LargeFlags<T, S> {
+    type Output = Self;
+    fn bitxor(mut self, rhs: T) -> Self{
+        self ^= rhs;
+        self
+    }
+}
+
+impl<T: Eq + Hash, const S: char> Add<Self> for LargeFlags<T, S> {
+    type Output = Self;
+    fn add(mut self, rhs: Self) -> Self{
+        self.0.extend(rhs);
+        self
+    }
+}
+
+impl<T: Eq + Hash, const S: char> Sub<Self> for LargeFlags<T, S> {
+    type Output = Self;
+    fn sub(mut self, rhs: Self) -> Self{
+        self -= rhs;
+        self
+    }
+}
+
+impl<T: Eq + Hash, const S: char> BitOr<Self> for LargeFlags<T, S> {
+    type Output = Self;
+    fn bitor(mut self, rhs: Self) -> Self{
+        self.0.extend(rhs);
+        self
+    }
+}
+
+impl<T: Eq + Hash, const S: char> BitAnd<Self> for LargeFlags<T, S> {
+    type Output = Self;
+    fn bitand(mut self, rhs: Self) -> Self{
+        self &= rhs;
+        self
+    }
+}
+
+impl<T: Eq + Hash, const S: char> BitXor<Self> for LargeFlags<T, S> {
+    type Output = Self;
+    fn bitxor(mut self, rhs: Self) -> Self{
+        self ^= rhs;
+        self
+    }
+}
+
+impl<T: Eq + Hash, const S: char> AddAssign<T> for LargeFlags<T, S> {
+    fn add_assign(&mut self, rhs: T) {
+        self.0.insert(rhs);
+    }
+}
+
+impl<T: Eq + Hash, const S: char> SubAssign<T> for LargeFlags<T, S> {
+    fn sub_assign(&mut self, rhs: T) {
+        self.0.remove(&rhs);
+    }
+}
+
+impl<T: Eq + Hash, const S: char> BitOrAssign<T> for LargeFlags<T, S> {
+    fn bitor_assign(&mut self, rhs: T) {
+        self.0.insert(rhs);
+    }
+}
+
+impl<T: Eq + Hash, const S: char> BitAndAssign<T> for LargeFlags<T, S> {
+    fn bitand_assign(&mut self, rhs: T) {
+        let found = self.0.contains(&rhs);
+        self.0.clear();
+        if found {
+            self.0.insert(rhs);
+        }
+    }
+}
+
+impl<T: Eq + Hash, const S: char> BitXorAssign<T> for LargeFlags<T, S> {
+    fn bitxor_assign(&mut self, rhs: T) {
+        if !self.0.remove(&rhs) {
+            self.0.insert(rhs);
+        }
+    }
+}
+
+impl<T: Eq + Hash, const S: char> AddAssign<Self> for LargeFlags<T, S> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0.extend(rhs)
+    }
+}
+
+impl<T: Eq + Hash, const S: char> SubAssign<Self> for LargeFlags<T, S> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0.retain(|x| !rhs.0.contains(x))
+    }
+}
+
+impl<T: Eq + Hash, const S: char> BitOrAssign<Self> for LargeFlags<T, S> {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0.extend(rhs)
+    }
+}
+
+impl<T: Eq + Hash, const S: char> BitAndAssign<Self> for LargeFlags<T, S> {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0.retain(|x| rhs.0.contains(x))
+    }
+}
+
+impl<T: Eq + Hash, const S: char> BitXorAssign<Self> for LargeFlags<T, S> {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        for i in rhs {
+            *self ^= i;
+        }
+    }
+}
@@ -25,8 +25,12 @@
 //! * `pub const Red: Color = "red";`
 //! * `pub const Green: Color = "green";`
 //! * `pub const DarkBlue: Color = "darkblue";`
+//! * `pub const VARIANTS: &[Color] = &[Color::Red, Color::Green, Color::DarkBlue];`
+//! * `pub const NAMES: &[&str] = &["red", "green", "darkblue"];`
 //!
-//! **Note:** The implementation is not `&'static str` and subject to change.
+//! Declared variants are stored as a pointer to static data,
+//! so they never allocate and have no length limit.
+//! `Color::new("Red")` resolves to the same static variant as `Color::Red`.
 //!
 //! ### And implements
 //!
@@ -55,11 +59,17 @@
 //! assert!(!flags.contains(Color::new("Black")));
 //! ```
 //!
+//! For sets with many items, [`LargeFlags`] is backed by a [`HashSet`](std::collections::HashSet),
+//! it has the same set predicates and the operators of [`Flags`] with a `LargeFlags` on the left.
+//!
 //! # Format
 //!
 //! We stores all data in [`flatlowercase`](https://docs.rs/convert_case/latest/convert_case/enum.Case.html#variant.Flat)
 //! to avoid case mismatches and some typos.
 //!
+//! Use `#[case = "..."]` on the type to select a different canonical case,
+//! see [`str_enum`] for details.
+//!
 //! # Serialization and Deserialization
 //!
 //! We use a string to serialize our "string enums".
@@ -72,6 +82,14 @@
 //!
 //! This ensures converting from enum to [`Flags`](crate::Flags) does not break serialization formats.
 //!
+//! Items are written in insertion order, add `#[canonical]` to the type to write
+//! declared variants in declaration order, followed by custom values sorted lexicographically.
+//! [`Flags::to_canonical_string`] always uses the canonical order.
+//!
+//! Alternative formats for [`Flags`](crate::Flags) are available in the `serde` module,
+//! e.g. `#[serde(with = "strflags::serde::seq")]` for `["dog", "cat", "giraffe"]`
+//! and `#[serde(with = "strflags::serde::bool_map")]` for `{ "dog": true, "cat": false }`.
+//!
 //! # The `debug` feature
 //!
 //! Allowing any string to be an enum variant is obviously prone to typos.
@@ -83,33 +101,105 @@
 //! This is obviously slow so be careful when using this feature.
 //!
 mod set;
+mod large;
+mod error;
 mod operators;
 mod query;
+mod pattern;
+mod separator;
+pub mod expr;
+mod repr;
+#[cfg(feature = "serde")]
+pub mod serde;
+use std::borrow::Cow;
 use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
 
-pub use set::{Flags, FlagsMarker};
-pub use query::Query;
+pub use set::{Flags, FlagsMarker, StrEnum};
+pub use large::LargeFlags;
+pub use error::{ParseFlagsError, UnknownVariant};
+pub use query::{All, Any, Lookup, Not, Query};
+pub use pattern::Pattern;
+pub use separator::{Lenient, Separator, SeparatorMarker};
+#[doc(hidden)]
+pub use repr::{Repr, Variant};
 #[doc(hidden)]
 pub use ecow::EcoString;
 #[doc(hidden)]
-pub use identconv::lower_strify;
+pub use strflags_derive::{__str_enum, __str_flags};
 #[doc(hidden)]
 pub use convert_case;
+#[cfg(feature = "bitflags")]
+#[doc(hidden)]
+pub use bitflags;
 
 
 static MATCH_RATIO: AtomicUsize = AtomicUsize::new(8);
 
-/// Sets the debug warning ratio, by default 8.
+/// Sets the typo detection ratio, by default 8.
 ///
 /// If the Levenshtein distance (number of edits) is less or equal to
-/// `sum_of_len / ratio` during compare, emit a "maybe typo" warning
-/// using the `log` crate.
+/// `sum_of_len / ratio`, two strings are considered similar.
+///
+/// This is used by `suggest` and [`UnknownVariant::suggestion`].
+/// If the `debug` feature is enabled, similar strings during compare
+/// also emit a "maybe typo" warning using the `log` crate.
 ///
-/// Only has an effect if the `debug` feature is enabled.
+/// A ratio of 0 disables suggestions and warnings.
+///
+/// ```
+/// # use ::strflags::*;
+/// # str_enum! { Method: [ Get, Post ] }
+/// assert_eq!(Method::suggest("posts"), Some(Method::Post));
+/// set_debug_match_ratio(0);
+/// assert_eq!(Method::suggest("posts"), None);
+/// assert_eq!(Method::try_new("posts").unwrap_err().suggestion(), None);
+/// ```
 pub fn set_debug_match_ratio(value: usize) {
     MATCH_RATIO.store(value, Relaxed)
 }
 
+/// Returns the Levenshtein distance if `a` and `b` are similar.
+fn similarity(a: &str, b: &str) -> Option<usize> {
+    let distance = levenshtein::levenshtein(a, b);
+    let max = (a.len() + b.len()).checked_div(MATCH_RATIO.load(Relaxed))?;
+    (distance <= max).then_some(distance)
+}
+
+/// Returns the index of the string in `names` most similar to `s`.
+#[doc(hidden)]
+pub fn closest(s: &str, names: &[&str]) -> Option<usize> {
+    names.iter()
+        .enumerate()
+        .filter_map(|(i, name)| similarity(s, name).map(|d| (d, i)))
+        .min()
+        .map(|(_, i)| i)
+}
+
+/// Converts `s` to `case`, without allocating if it is already converted.
+#[doc(hidden)]
+pub fn normalize(s: &str, case: Option<convert_case::Case>) -> Cow<'_, str> {
+    use convert_case::{Case, Casing};
+    let words = |sep: char, upper: bool| s.split(sep).all(|word| {
+        !word.is_empty() && word.bytes().all(|b| if upper {
+            b.is_ascii_uppercase()
+        } else {
+            b.is_ascii_lowercase()
+        })
+    });
+    let normalized = match case {
+        None => true,
+        Some(Case::Flat) => s.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit()),
+        Some(Case::Snake) => words('_', false),
+        Some(Case::Kebab) => words('-', false),
+        Some(Case::ScreamingSnake) => words('_', true),
+        Some(_) => false,
+    };
+    match case {
+        Some(case) if !normalized => Cow::Owned(s.to_case(case)),
+        _ => Cow::Borrowed(s),
+    }
+}
+
 #[cfg(not(feature = "debug"))]
 #[doc(hidden)]
 #[inline(always)]
@@ -120,9 +210,10 @@ pub fn str_eq(a: &str, b: &str) -> bool {
 #[cfg(feature = "debug")]
 #[doc(hidden)]
 #[inline(always)]
+#[allow(clippy::collapsible_if)]
 pub fn str_eq(a: &str, b: &str) -> bool {
     if a.len() > 3 && b.len() > 3 {
-        if levenshtein::levenshtein(a, b) <= (a.len() + b.len()) / MATCH_RATIO.load(Relaxed) {
+        if similarity(a, b).is_some() {
             log::warn!("{} and {} are similar, maybe a typo?", a, b)
         }
     }
@@ -134,125 +225,316 @@ pub fn str_eq(a: &str, b: &str) -> bool {
 /// To use [`Flags`], call [`str_flags`] instead.
 ///
 /// This struct stores all its data in `flatlowercase` to avoid some typos.
+/// This can be changed with `#[case = "..."]` on the type, one of
+///
+/// * `"flatcase"`, the default
+/// * `"snake_case"`
+/// * `"kebab-case"`
+/// * `"SCREAMING_SNAKE_CASE"`
+/// * `"verbatim"`, no conversion at all
+///
+/// The case is applied to declared variants, `new`, `Display` and serialization.
+///
+/// ```
+/// # use ::strflags::*;
+/// str_enum! {
+///     #[case = "snake_case"]
+///     pub Color: [
+///         Red,
+///         DarkBlue,
+///     ]
+/// }
+///
+/// assert_eq!(Color::DarkBlue.to_string(), "dark_blue");
+/// assert_eq!(Color::new("dark-blue"), Color::DarkBlue);
+/// assert_eq!(Color::new("LightBlue").to_string(), "light_blue");
+/// ```
+///
+/// Declared variants are static and can be of any length,
+/// other strings are stored in an [`EcoString`](https://docs.rs/ecow/latest/ecow/string/struct.EcoString.html).
+///
+/// Two variants that convert to the same string is a compile time error.
+///
+/// ```compile_fail
+/// # use ::strflags::*;
+/// str_enum! {
+///     Color: [
+///         DarkBlue,
+///         Darkblue,
+///     ]
+/// }
+/// ```
+///
+/// # Aliases
+///
+/// Extra accepted spellings can be listed after a variant,
+/// they are converted to `flatlowercase` like any other input.
 ///
-/// Currently we use [`EcoString`](https://docs.rs/ecow/latest/ecow/string/struct.EcoString.html)
-/// which can only inline 15 bytes. Having a larger compile time constant string
-/// is a ***runtime*** error if the constant is **USED**.
+/// ```
+/// # use ::strflags::*;
+/// str_enum! {
+///     pub Language: [
+///         Rust,
+///         CPlusPlus = ["c++", "cpp"],
+///     ]
+/// }
 ///
-/// However loading a larger string at runtime is fine.
+/// assert_eq!(Language::new("C++"), Language::CPlusPlus);
+/// assert_eq!(Language::new("cpp"), Language::CPlusPlus);
+/// assert_eq!(Language::new("cpp").to_string(), "cplusplus");
+/// ```
 ///
-/// This behavior will be changed in the future.
+/// # Explicit values
+///
+/// A variant can be given an arbitrary string instead of its `flatlowercase` name.
+/// The string is stored as is, while inputs are still matched after case conversion.
+/// Aliases can follow an explicit value.
+///
+/// ```
+/// # use ::strflags::*;
+/// str_enum! {
+///     pub Mime: [
+///         Html = "text/html" ["html"],
+///         Json = "application/json",
+///         FormUrlEncoded = "application/x-www-form-urlencoded",
+///     ]
+/// }
+///
+/// assert_eq!(Mime::Html.to_string(), "text/html");
+/// assert_eq!(Mime::new("html"), Mime::Html);
+/// assert_eq!(Mime::new("application/json"), Mime::Json);
+/// assert_eq!(Mime::new("application/x-www-form-urlencoded"), Mime::FormUrlEncoded);
+/// ```
+///
+/// # Labels
+///
+/// `label` returns a human readable name, set with `#[label("...")]`
+/// or derived from the identifier.
+///
+/// ```
+/// # use ::strflags::*;
+/// str_enum! {
+///     #[case = "snake_case"]
+///     pub Color: [
+///         #[label("Sky Blue")]
+///         Cyan,
+///         DarkBlue,
+///     ]
+/// }
+///
+/// assert_eq!(Color::Cyan.label(), "Sky Blue");
+/// assert_eq!(Color::DarkBlue.label(), "Dark Blue");
+/// assert_eq!(Color::new("light_green").label(), "Light Green");
+/// ```
+///
+/// # Strict
+///
+/// `new` accepts any string, use `try_new` to reject undeclared variants.
+///
+/// Adding `#[strict]` to the type makes [`FromStr`](std::str::FromStr)
+/// and `Deserialize` reject undeclared variants with [`UnknownVariant`].
+///
+/// ```
+/// # use ::strflags::*;
+/// str_enum! {
+///     #[strict]
+///     pub Color: [
+///         Red,
+///         Green,
+///     ]
+/// }
+///
+/// assert_eq!("Red".parse(), Ok(Color::Red));
+/// assert!("Blue".parse::<Color>().is_err());
+/// assert!(Color::try_new("Blue").is_err());
+/// assert_eq!(Color::new("Blue"), "blue");
+/// ```
+///
+/// # Suggestions
+///
+/// `suggest` finds the declared variant closest to a string,
+/// [`UnknownVariant`] also reports it.
+/// See [`set_debug_match_ratio`] for the threshold.
+///
+/// ```
+/// # use ::strflags::*;
+/// # str_enum! { #[strict] pub Color: [ Red, Green ] }
+/// assert_eq!(Color::suggest("Gren"), Some(Color::Green));
+/// assert_eq!(Color::suggest("Blue"), None);
+///
+/// let err = Color::try_new("Gren").unwrap_err();
+/// assert_eq!(err.suggestion(), Some("green"));
+/// ```
 #[macro_export]
 macro_rules! str_enum {
-    ($(#[$main_attr:meta])* $vis:vis $name: ident: [$($(#[$attr: meta])* $fields: ident),* $(,)?]) => {
-        
-        #[derive(Debug, Clone, Eq, Hash)]
-        $(#[$main_attr])*
-        $vis struct $name(::strflags::EcoString);
-
-        const _: () = {
-
-            #[allow(non_upper_case_globals)]
-            impl $name {
-                $($(#[$attr])*
-                pub const $fields: Self = Self(::strflags::EcoString::inline(
-                    ::strflags::lower_strify!($fields)
-                ));)*
-
-                #[inline]
-                pub fn new(s: &str) -> Self {
-                    use ::strflags::convert_case::{Casing, Case::*};
-                    Self(s.to_case(Flat).into())
-                }
-            }
-
-            impl ::std::fmt::Display for $name {
-                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> std::fmt::Result {
-                    f.write_str(self.0.as_str())
-                }
-            }
-
-            impl ::std::str::FromStr for $name {
-                type Err=::std::convert::Infallible;
-
-                fn from_str(s: &str) -> Result<Self, Self::Err>{
-                    Ok(Self::new(s))
-                }
-            }
-
-            impl Into<::strflags::Flags<Self>> for $name {
-                fn into(self) -> ::strflags::Flags<Self> {
-                    ::strflags::Flags::new(self)
-                }
-            }
-
-            impl std::borrow::Borrow<str> for $name {
-                fn borrow(&self) -> &str {
-                    use std::borrow::Borrow;
-                    self.0.borrow()
-                }
-            }
-
-            impl AsRef<str> for $name {
-                fn as_ref(&self) -> &str {
-                    self.0.as_ref()
-                }
-            }
-
-            impl PartialEq<str> for $name {
-                fn eq(&self, other: &str) -> bool {
-                    self.0 == other
-                }
-            }
-
-            impl<T: AsRef<str>> ::std::cmp::PartialEq<T> for $name {
-                fn eq(&self, other: &T) -> bool {
-                    ::strflags::str_eq(self.0.as_ref(), other.as_ref())
-                }
-            }
-
-        };
-
-        ::strflags::impl_serde!($name);
+    ($($tt: tt)*) => {
+        ::strflags::__str_enum!{$($tt)*}
     };
 }
 
 /// Construct a string enum and enable [`Flags`] usage.
 ///
 /// This provides all functionalites of [`str_enum`].
+///
+/// # Canonical order
+///
+/// Adding `#[canonical]` to the type makes [`Flags`] display and serialize
+/// declared variants in declaration order, followed by custom values sorted lexicographically.
+///
+/// ```
+/// # use ::strflags::*;
+/// str_flags! {
+///     #[canonical]
+///     pub Color: [
+///         Red,
+///         Green,
+///         Blue,
+///     ]
+/// }
+///
+/// let flags = Color::new("Yellow") | Color::Blue | Color::new("Black") | Color::Red;
+/// assert_eq!(flags.to_string(), "red|blue|black|yellow");
+/// ```
+///
+/// # Hierarchy
+///
+/// A variant can list the variants it implies, queries like [`Flags::contains`]
+/// then treat them as present. See [`Flags::expand`] and [`Flags::compact`].
+///
+/// ```
+/// # use ::strflags::*;
+/// str_flags! {
+///     pub Permission: [
+///         Admin: [Write, Delete],
+///         Write: [Read],
+///         Read,
+///         Delete,
+///     ]
+/// }
+///
+/// let flags: Flags<Permission> = Flags::new(Permission::Write);
+/// assert!(flags.contains(Permission::Read));
+/// assert!(!flags.contains(Permission::Delete));
+/// assert_eq!(flags.len(), 1);
+/// ```
+///
+/// Implications must not form a cycle.
+///
+/// ```compile_fail
+/// # use ::strflags::*;
+/// str_flags! {
+///     Permission: [
+///         Read: [Write],
+///         Write: [Read],
+///     ]
+/// }
+/// ```
+///
+/// # Separator
+///
+/// `#[separator(';')]` sets the separator used by the `|` operators and
+/// generates an alias `ColorFlags` for `Flags<Color, ';'>`.
+/// The separator can be any character other than `\`, which is used for escaping.
+///
+/// Multi-character separators and [`Lenient`] cannot be declared on the type,
+/// use [`Flags::parse_with`] and [`Flags::display_with`] instead,
+/// or `strflags::serde::Sep` with the `serde` feature.
+///
+/// ```
+/// # use ::strflags::*;
+/// str_flags! {
+///     #[separator(';')]
+///     pub Color: [
+///         Red,
+///         Green,
+///     ]
+/// }
+///
+/// let flags: ColorFlags = Color::Red | Color::new("a;b");
+/// assert_eq!(flags.to_string(), r"red;a\;b");
+/// assert_eq!(flags.to_string().parse::<ColorFlags>().unwrap(), flags);
+/// ```
+///
+/// # Bitflags
+///
+/// With the `bitflags` feature enabled, `#[bitflags(ColorBits)]` generates a
+/// [`bitflags`](https://docs.rs/bitflags) type with a flag for each declared variant,
+/// named in `SCREAMING_SNAKE_CASE`, and conversions to and from [`Flags`].
+///
+/// Converting with `TryFrom` fails on custom values, `from_flags_truncate` drops them.
+///
+/// ```
+/// # #[cfg(feature = "bitflags")] {
+/// # use ::strflags::*;
+/// str_flags! {
+///     #[bitflags(ColorBits)]
+///     pub Color: [
+///         Red,
+///         Green,
+///         DarkBlue,
+///     ]
+/// }
+///
+/// let bits = ColorBits::RED | ColorBits::DARK_BLUE;
+/// assert_eq!(Flags::from(bits), Color::Red | Color::DarkBlue);
+/// assert_eq!(ColorBits::try_from(Color::Red | Color::DarkBlue), Ok(bits));
+///
+/// let flags = Color::Red | Color::new("Yellow");
+/// assert!(ColorBits::try_from(flags.clone()).is_err());
+/// assert_eq!(ColorBits::from_flags_truncate(&flags), ColorBits::RED);
+/// # }
+/// ```
 #[macro_export]
 macro_rules! str_flags {
-    ($(#[$main_attr: meta])* $vis:vis $name: ident: [$($(#[$attr: meta])* $fields: ident),* $(,)?]) => {
-        ::strflags::str_enum!($(#[$main_attr])* $vis $name: [$($(#[$attr])* $fields),*]);
+    ($($tt: tt)*) => {
+        ::strflags::__str_flags!{$($tt)*}
+    };
+}
 
-        const _: () = {
-            impl ::strflags::FlagsMarker for $name {}
+#[cfg(not(feature="bitflags"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_bitflags {
+    ($($tt: tt)*) => {
+        compile_error!("`#[bitflags]` requires the `bitflags` feature of `strflags`");
+    }
+}
 
-            impl ::std::ops::BitOr for $name {
-                type Output = ::strflags::Flags<Self>;
-                fn bitor(self, rhs: Self) -> Self::Output {
-                    ::strflags::Flags::pair(self, rhs)
-                }
+#[cfg(feature="bitflags")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_bitflags {
+    ($vis: vis $bits: ident: $ty: ty, $name: ident, $sep: literal, [$($flag: ident = $value: expr),*]) => {
+        ::strflags::bitflags::bitflags! {
+            #[doc = concat!("Declared variants of [`", stringify!($name), "`] as bitflags.")]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            $vis struct $bits: $ty {
+                $(const $flag = $value;)*
             }
+        }
 
-            impl ::std::ops::BitOr<::strflags::Flags<Self>> for $name {
-                type Output = ::strflags::Flags<Self>;
-                fn bitor(self, rhs: ::strflags::Flags<Self>) -> Self::Output {
-                    rhs | self
+        const _: () = {
+            impl $bits {
+                /// Converts the declared variants in `flags`, custom values are dropped.
+                pub fn from_flags_truncate(flags: &::strflags::Flags<$name, $sep>) -> Self {
+                    Self::from_bits_truncate(flags.known_bits() as $ty)
                 }
             }
 
-            impl ::std::ops::BitAnd<::strflags::Flags<Self>> for $name {
-                type Output = ::strflags::Flags<Self>;
-                fn bitand(self, rhs: ::strflags::Flags<Self>) -> Self::Output {
-                    rhs & self
+            impl ::std::convert::From<$bits> for ::strflags::Flags<$name, $sep> {
+                fn from(bits: $bits) -> Self {
+                    ::strflags::Flags::from_known_bits(bits.bits() as u128)
                 }
             }
 
-            impl ::std::ops::BitXor<::strflags::Flags<Self>> for $name {
-                type Output = ::strflags::Flags<Self>;
-                fn bitxor(self, rhs: ::strflags::Flags<Self>) -> Self::Output {
-                    rhs ^ self
+            impl ::std::convert::TryFrom<::strflags::Flags<$name, $sep>> for $bits {
+                type Error = ::strflags::UnknownVariant;
+
+                fn try_from(flags: ::strflags::Flags<$name, $sep>) -> Result<Self, Self::Error> {
+                    match flags.iter().find_map(|x| $name::try_new(x.as_ref()).err()) {
+                        Some(err) => Err(err),
+                        None => Ok(Self::from_flags_truncate(&flags)),
+                    }
                 }
             }
         };
@@ -276,14 +558,14 @@ macro_rules! impl_serde {
             use ::serde::{Serialize, Deserialize};
             impl Serialize for $name {
                 fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: ::serde::Serializer {
-                    self.0.serialize(serializer)
+                    self.0.as_str().serialize(serializer)
                 }
             }
 
             impl<'de> Deserialize<'de> for $name {
                 fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: ::serde::Deserializer<'de> {
                     let s = <::std::borrow::Cow<str>>::deserialize(deserializer)?;
-                    Ok(Self::new(s.as_ref()))
+                    s.parse().map_err(::serde::de::Error::custom)
                 }
             }
         };
@@ -1,211 +1,151 @@
-use crate::Flags;
-use smallvec::SmallVec;
+use crate::{Flags, StrEnum};
+use crate::set::bit;
 use std::ops::*;
 
-impl<T: PartialEq, const S: char> Add<T> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> Add<T> for Flags<T, S> {
     type Output = Self;
     fn add(mut self, rhs: T) -> Self{
-        if !self.0.iter().any(|x| x == &rhs){
-            self.0.push(rhs)
-        }
+        self.insert(rhs);
         self
     }
 }
 
-impl<T: PartialEq, const S: char> Sub<T> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> Sub<T> for Flags<T, S> {
     type Output = Self;
     fn sub(mut self, rhs: T) -> Self{
-        if let Some(index) = self.0.iter().position(|x| x == &rhs){
-            self.0.remove(index);
-        }
+        self.remove(&rhs);
         self
     }
 }
 
-impl<T: PartialEq, const S: char> BitOr<T> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> BitOr<T> for Flags<T, S> {
     type Output = Self;
     fn bitor(mut self, rhs: T) -> Self{
-        if !self.0.iter().any(|x| x == &rhs){
-            self.0.push(rhs)
-        }
+        self.insert(rhs);
         self
     }
 }
 
-impl<T: PartialEq, const S: char> BitAnd<T> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> BitAnd<T> for Flags<T, S> {
     type Output = Self;
-    fn bitand(self, rhs: T) -> Self{
-        if self.0.iter().any(|x| x == &rhs){
-            Self::new(rhs)
-        } else {
-            Self::EMPTY
-        }
+    fn bitand(mut self, rhs: T) -> Self{
+        self &= rhs;
+        self
     }
 }
 
-impl<T: PartialEq, const S: char> BitXor<T> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> BitXor<T> for Flags<T, S> {
     type Output = Self;
     fn bitxor(mut self, rhs: T) -> Self{
-        if let Some(index) = self.0.iter().position(|x| x == &rhs){
-            self.0.remove(index);
-        } else {
-            self.0.push(rhs)
-        }
+        self ^= rhs;
         self
     }
 }
 
-impl<T: PartialEq, const S: char> Add<Self> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> Add<Self> for Flags<T, S> {
     type Output = Self;
     fn add(mut self, rhs: Self) -> Self{
-        for i in rhs.into_iter(){
-            if !self.0.iter().any(|x| x == &i){
-                self.0.push(i)
-            }
-        }
+        self.union(rhs);
         self
     }
 }
 
-impl<T: PartialEq, const S: char> Sub<Self> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> Sub<Self> for Flags<T, S> {
     type Output = Self;
     fn sub(mut self, rhs: Self) -> Self{
-        for i in rhs.into_iter(){
-            if let Some(index) = self.0.iter().position(|x| x == &i){
-                self.0.remove(index);
-            }
-        }
+        self -= rhs;
         self
     }
 }
 
-impl<T: PartialEq, const S: char> BitOr<Self> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> BitOr<Self> for Flags<T, S> {
     type Output = Self;
     fn bitor(mut self, rhs: Self) -> Self{
-        for i in rhs.into_iter(){
-            if !self.0.iter().any(|x| x == &i){
-                self.0.push(i)
-            }
-        }
+        self |= rhs;
         self
     }
 }
 
-impl<T: PartialEq, const S: char> BitAnd<Self> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> BitAnd<Self> for Flags<T, S> {
     type Output = Self;
-    fn bitand(self, rhs: Self) -> Self{
-        Self(self.0.into_iter()
-                .filter(|l| rhs.iter().any(|r| r == l))
-                .collect())
+    fn bitand(mut self, rhs: Self) -> Self{
+        self &= rhs;
+        self
     }
 }
 
-impl<T: PartialEq, const S: char> BitXor<Self> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> BitXor<Self> for Flags<T, S> {
     type Output = Self;
     fn bitxor(mut self, rhs: Self) -> Self{
-        for i in rhs.into_iter(){
-            if let Some(pos) = self.0.iter().position(|x| x == &i) {
-                self.0.remove(pos);
-            } else {
-                self.0.push(i);
-            }
-        }
+        self ^= rhs;
         self
     }
 }
 
-impl<T: PartialEq, const S: char> AddAssign<T> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> AddAssign<T> for Flags<T, S> {
     fn add_assign(&mut self, rhs: T) {
-        if !self.0.iter().any(|x| x == &rhs){
-            self.0.push(rhs)
-        }
+        self.insert(rhs);
     }
 }
 
-impl<T: PartialEq, const S: char> SubAssign<T> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> SubAssign<T> for Flags<T, S> {
     fn sub_assign(&mut self, rhs: T) {
-        if let Some(index) = self.0.iter().position(|x| x == &rhs){
-            self.0.remove(index);
-        }
+        self.remove(&rhs);
     }
 }
 
-impl<T: PartialEq, const S: char> BitOrAssign<T> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> BitOrAssign<T> for Flags<T, S> {
     fn bitor_assign(&mut self, rhs: T) {
-        if !self.0.iter().any(|x| x == &rhs){
-            self.0.push(rhs)
-        }
+        self.insert(rhs);
     }
 }
 
-impl<T: PartialEq, const S: char> BitAndAssign<T> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> BitAndAssign<T> for Flags<T, S> {
     fn bitand_assign(&mut self, rhs: T) {
-        if self.0.iter().any(|x| x == &rhs){
-            *self = Self(SmallVec::new_const());
-            self.0.push(rhs)
-        } else {
-            *self = Self(SmallVec::new_const());
+        match bit(&rhs) {
+            Some(rhs) => self.retain(|x| bit(x) == Some(rhs)),
+            None => self.retain(|x| bit(x).is_none() && x == &rhs),
         }
     }
 }
 
-impl<T: PartialEq, const S: char> BitXorAssign<T> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> BitXorAssign<T> for Flags<T, S> {
     fn bitxor_assign(&mut self, rhs: T) {
-        if let Some(index) = self.0.iter().position(|x| x == &rhs){
-            self.0.remove(index);
-        } else {
-            self.0.push(rhs)
+        if !self.remove(&rhs) {
+            self.insert(rhs);
         }
     }
 }
 
-impl<T: PartialEq, const S: char> AddAssign<Self> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> AddAssign<Self> for Flags<T, S> {
     fn add_assign(&mut self, rhs: Self) {
-        for i in rhs.into_iter(){
-            if !self.0.iter().any(|x| x == &i){
-                self.0.push(i)
-            }
-        }
+        self.union(rhs);
     }
 }
 
-impl<T: PartialEq, const S: char> SubAssign<Self> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> SubAssign<Self> for Flags<T, S> {
     fn sub_assign(&mut self, rhs: Self) {
-        for i in rhs.into_iter(){
-            if let Some(index) = self.0.iter().position(|x| x == &i){
-                self.0.remove(index);
-            }
-        }
+        self.difference(&rhs);
     }
 }
 
-impl<T: PartialEq, const S: char> BitOrAssign<Self> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> BitOrAssign<Self> for Flags<T, S> {
     fn bitor_assign(&mut self, rhs: Self) {
-        for i in rhs.into_iter(){
-            if !self.0.iter().any(|x| x == &i){
-                self.0.push(i)
-            }
-        }
+        self.union(rhs);
     }
 }
 
-impl<T: PartialEq, const S: char> BitAndAssign<Self> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> BitAndAssign<Self> for Flags<T, S> {
     fn bitand_assign(&mut self, rhs: Self) {
-        *self = Self(
-            std::mem::take(&mut self.0)
-                .into_iter()
-                .filter(|l| rhs.iter().any(|r| r == l))
-                .collect())
+        self.intersect(&rhs);
     }
 }
 
-impl<T: PartialEq, const S: char> BitXorAssign<Self> for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> BitXorAssign<Self> for Flags<T, S> {
     fn bitxor_assign(&mut self, rhs: Self) {
-        for i in rhs.into_iter(){
-            if let Some(pos) = self.0.iter().position(|x| x == &i) {
-                self.0.remove(pos);
-            } else {
-                self.0.push(i);
+        for item in rhs {
+            if !self.remove(&item) {
+                self.insert(item);
             }
         }
     }
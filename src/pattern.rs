@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+
+use crate::query::Lookup;
+use crate::{FlagsMarker, Query, StrEnum};
+
+/// A wildcard [`Query`], matching items by prefix, suffix or glob.
+///
+/// When used as a query, the pattern is converted to the canonical case of the type,
+/// keeping `*` and `?` as wildcards, so `Pattern::prefix("Region")` finds `regioneu`.
+///
+/// ```
+/// # use strflags::*;
+/// # str_flags! { Tag: [ RegionEu, RegionUs, Beta ] }
+/// let tags = Tag::RegionEu | Tag::Beta | Tag::new("RegionAsia");
+/// assert!(tags.contains(Pattern::prefix("Region")));
+/// assert!(tags.contains(Pattern::glob("region??")));
+/// assert!(!tags.contains(Pattern::suffix("us")));
+/// assert_eq!(tags.filter_matching(&Pattern::glob("region*")), Tag::RegionEu | Tag::new("RegionAsia"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Pattern {
+    /// Items starting with the string.
+    Prefix(String),
+    /// Items ending with the string.
+    Suffix(String),
+    /// Glob pattern, `*` matches any sequence of characters and `?` matches a single character.
+    Glob(String),
+}
+
+impl Pattern {
+    #[inline]
+    pub fn prefix(s: impl Into<String>) -> Self {
+        Pattern::Prefix(s.into())
+    }
+
+    #[inline]
+    pub fn suffix(s: impl Into<String>) -> Self {
+        Pattern::Suffix(s.into())
+    }
+
+    #[inline]
+    pub fn glob(s: impl Into<String>) -> Self {
+        Pattern::Glob(s.into())
+    }
+
+    /// Returns this pattern converted to the canonical case of `T`,
+    /// keeping `*` and `?` as wildcards.
+    pub fn normalized<T: StrEnum>(&self) -> Cow<'_, Pattern> {
+        let converted = match self {
+            Pattern::Prefix(prefix) => convert::<T>(prefix, "", "*").map(Pattern::Prefix),
+            Pattern::Suffix(suffix) => convert::<T>(suffix, "*", "").map(Pattern::Suffix),
+            Pattern::Glob(glob) => convert::<T>(glob, "", "").map(Pattern::Glob),
+        };
+        converted.map_or(Cow::Borrowed(self), Cow::Owned)
+    }
+
+    /// Returns `true` if `s` matches this pattern exactly as written.
+    pub fn is_match(&self, s: &str) -> bool {
+        match self {
+            Pattern::Prefix(prefix) => s.starts_with(prefix.as_str()),
+            Pattern::Suffix(suffix) => s.ends_with(suffix.as_str()),
+            Pattern::Glob(glob) => glob_match(glob, s),
+        }
+    }
+}
+
+/// Converts `s` to the case of `T`, with the wildcards `before` and `after` around it
+/// so separators next to them are kept. Returns `None` if `s` is unchanged.
+fn convert<T: StrEnum>(s: &str, before: &str, after: &str) -> Option<String> {
+    if let Cow::Borrowed(_) = T::normalize(s) {
+        return None;
+    }
+    let converted = T::normalize(&format!("{}{}{}", before, s, after)).into_owned();
+    let trimmed = converted.strip_prefix(before)
+        .and_then(|x| x.strip_suffix(after))
+        .unwrap_or(&converted);
+    (trimmed != s).then(|| trimmed.to_owned())
+}
+
+/// Match `s` against `glob`, backtracking to the last `*` on mismatch.
+fn glob_match(glob: &str, s: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let s: Vec<char> = s.chars().collect();
+    let (mut g, mut i) = (0, 0);
+    let mut star = None;
+    while i < s.len() {
+        match glob.get(g) {
+            Some('*') => {
+                star = Some((g, i));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == s[i] => {
+                g += 1;
+                i += 1;
+            }
+            _ => match star {
+                Some((star_g, star_i)) => {
+                    star = Some((star_g, star_i + 1));
+                    g = star_g + 1;
+                    i = star_i + 1;
+                }
+                None => return false,
+            }
+        }
+    }
+    glob[g..].iter().all(|c| *c == '*')
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Prefix(prefix) => write!(f, "{}*", prefix),
+            Pattern::Suffix(suffix) => write!(f, "*{}", suffix),
+            Pattern::Glob(glob) => f.write_str(glob),
+        }
+    }
+}
+
+/// Matches if any item of the set matches the pattern.
+impl<T: FlagsMarker> Query<T> for Pattern {
+    type Iter<'t> = std::iter::Empty<&'t str>;
+    fn items<'t>(&'t self) -> Self::Iter<'t> {
+        std::iter::empty()
+    }
+    fn matches<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        let pattern = self.normalized::<T>();
+        set.any_str(|s| pattern.is_match(s))
+    }
+    fn matches_all<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        Query::<T>::matches(self, set)
+    }
+    fn is_match(&self, s: &str) -> bool {
+        self.normalized::<T>().is_match(s)
+    }
+}
+
+impl<T: FlagsMarker> Query<T> for &Pattern {
+    type Iter<'t> = std::iter::Empty<&'t str> where Self: 't;
+    fn items<'t>(&'t self) -> Self::Iter<'t> {
+        std::iter::empty()
+    }
+    fn matches<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        Query::<T>::matches(*self, set)
+    }
+    fn matches_all<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        Query::<T>::matches(*self, set)
+    }
+    fn is_match(&self, s: &str) -> bool {
+        Query::<T>::is_match(*self, s)
+    }
+}
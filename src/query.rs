@@ -1,25 +1,232 @@
-use std::borrow::Cow;
+use std::borrow::{Borrow, Cow};
+use std::hash::Hash;
 use std::marker::PhantomData;
 
 use crate::EcoString;
 use crate::Flags;
 use crate::FlagsMarker;
+use crate::LargeFlags;
+use crate::StrEnum;
+use crate::set::{bit, Iter};
 
 /// Items that can be used in [`Flags::contains()`](crate::Flags::contains)
-/// 
-/// # Warning
-/// 
-/// Although a few string types are supported,
-/// their usage is not encouraged, since
-/// we do not perform case conversion in `contains`.
-/// 
-/// The user should take care to proivde only `flatlowercase` strings.
+///
+/// String queries are converted like `new`, so `flags.contains("DarkBlue")`
+/// finds `Color::DarkBlue`. Strings already in the canonical case
+/// are looked up without allocating.
 pub trait Query<T: FlagsMarker> {
+    /// If `true`, items are converted with [`StrEnum::new`] before lookup.
+    const RAW: bool = false;
+
     type Iter<'t>: Iterator<Item = &'t str> where Self: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t>;
+
+    /// Bitmask of the queried items, if all of them are declared variants.
+    ///
+    /// Allows [`Flags`] to answer the query with a single integer operation.
+    #[inline]
+    fn mask(&self) -> Option<u128> {
+        None
+    }
+
+    /// Returns `true` if any item is in `set`.
+    #[inline]
+    fn matches<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        match (self.mask(), set.known_bits()) {
+            (Some(mask), Some(known)) => known & mask != 0,
+            _ => self.items().any(|i| lookup::<T, _>(i, Self::RAW, set)),
+        }
+    }
+
+    /// Returns `true` if every item is in `set`.
+    #[inline]
+    fn matches_all<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        match (self.mask(), set.known_bits()) {
+            (Some(mask), Some(known)) => known & mask == mask,
+            _ => self.items().all(|i| lookup::<T, _>(i, Self::RAW, set)),
+        }
+    }
+
+    /// Returns `true` if `s`, an item of a set, is matched by this query.
+    #[inline]
+    fn is_match(&self, s: &str) -> bool {
+        self.items().any(|i| i == s || Self::RAW && T::new(i).as_ref() == s)
+    }
+}
+
+/// Returns `true` if `s` is in `set`, if `raw`, also tries `s` converted by [`StrEnum::new`].
+fn lookup<T: StrEnum, S: Lookup<T> + ?Sized>(s: &str, raw: bool, set: &S) -> bool {
+    if set.contains_str(s) {
+        return true;
+    }
+    if !raw {
+        return false;
+    }
+    let item = T::new(s);
+    item.as_ref() != s && set.contains_str(item.as_ref())
+}
+
+/// Sets a [`Query`] can be evaluated against,
+/// implemented by [`Flags`] and [`LargeFlags`].
+pub trait Lookup<T> {
+    /// Returns `true` if an item equal to `s` is in the set.
+    fn contains_str(&self, s: &str) -> bool;
+
+    /// Returns `true` if `f` returns `true` for any item in the set.
+    fn any_str(&self, f: impl FnMut(&str) -> bool) -> bool;
+
+    /// Bitmask of declared variants, see [`Flags::known_bits`].
+    ///
+    /// `None` if the set does not track declared variants separately.
+    #[inline]
+    fn known_bits(&self) -> Option<u128> {
+        None
+    }
+}
+
+impl<T: StrEnum + PartialEq, const S: char> Lookup<T> for Flags<T, S> {
+    #[inline]
+    fn contains_str(&self, s: &str) -> bool {
+        self.iter_implied().any(|x| x == s)
+    }
+
+    #[inline]
+    fn any_str(&self, mut f: impl FnMut(&str) -> bool) -> bool {
+        self.iter_implied().any(|x| f(x.as_ref()))
+    }
+
+    #[inline]
+    fn known_bits(&self) -> Option<u128> {
+        Some(self.implied_bits())
+    }
+}
+
+impl<T: Eq + Hash + Borrow<str>, const S: char> Lookup<T> for LargeFlags<T, S> {
+    #[inline]
+    fn contains_str(&self, s: &str) -> bool {
+        self.0.contains(s)
+    }
+
+    #[inline]
+    fn any_str(&self, mut f: impl FnMut(&str) -> bool) -> bool {
+        self.0.iter().any(|x| f(x.borrow()))
+    }
+}
+
+/// Matches if every item of the inner query is in the set.
+///
+/// ```
+/// # use strflags::*;
+/// # str_flags! { Color: [ Red, Green, Blue ] }
+/// let flags = Color::Red | Color::Green;
+/// assert!(flags.contains(All(Color::Red | Color::Green)));
+/// assert!(!flags.contains(All(Color::Red | Color::Blue)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct All<Q>(pub Q);
+
+/// Matches if any item of the inner query is in the set,
+/// this is the default behavior of [`Flags::contains`].
+#[derive(Debug, Clone, Copy)]
+pub struct Any<Q>(pub Q);
+
+/// Matches if the inner query does not match.
+///
+/// ```
+/// # use strflags::*;
+/// # str_flags! { Color: [ Red, Green, Blue ] }
+/// let flags = Color::Red | Color::Green;
+/// assert!(flags.contains(Not(Color::Blue)));
+/// assert!(flags.contains(Not(All(Color::Red | Color::Blue))));
+/// assert!(flags.contains((Color::Blue, All(Color::Red | Color::Green))));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Not<Q>(pub Q);
+
+impl<T: FlagsMarker, Q: Query<T>> Query<T> for All<Q> {
+    type Iter<'t> = Q::Iter<'t> where Self: 't;
+    fn items<'t>(&'t self) -> Self::Iter<'t> {
+        self.0.items()
+    }
+    fn matches<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        self.0.matches_all(set)
+    }
+    fn matches_all<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        self.0.matches_all(set)
+    }
+    fn is_match(&self, s: &str) -> bool {
+        self.0.is_match(s)
+    }
+}
+
+impl<T: FlagsMarker, Q: Query<T>> Query<T> for Any<Q> {
+    type Iter<'t> = Q::Iter<'t> where Self: 't;
+    fn items<'t>(&'t self) -> Self::Iter<'t> {
+        self.0.items()
+    }
+    fn matches<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        self.0.matches(set)
+    }
+    fn matches_all<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        self.0.matches(set)
+    }
+    fn is_match(&self, s: &str) -> bool {
+        self.0.is_match(s)
+    }
+}
+
+impl<T: FlagsMarker, Q: Query<T>> Query<T> for Not<Q> {
+    type Iter<'t> = Q::Iter<'t> where Self: 't;
+    fn items<'t>(&'t self) -> Self::Iter<'t> {
+        self.0.items()
+    }
+    fn matches<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        !self.0.matches(set)
+    }
+    fn matches_all<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        !self.0.matches(set)
+    }
+    fn is_match(&self, s: &str) -> bool {
+        !self.0.is_match(s)
+    }
+}
+
+/// A tuple matches if any of its queries match,
+/// or with [`All`], if all of its queries match.
+impl<T: FlagsMarker, A: Query<T>, B: Query<T>> Query<T> for (A, B) {
+    type Iter<'t> = std::iter::Chain<A::Iter<'t>, B::Iter<'t>> where Self: 't;
+    fn items<'t>(&'t self) -> Self::Iter<'t> {
+        self.0.items().chain(self.1.items())
+    }
+    fn matches<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        self.0.matches(set) || self.1.matches(set)
+    }
+    fn matches_all<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        self.0.matches(set) && self.1.matches(set)
+    }
+    fn is_match(&self, s: &str) -> bool {
+        self.0.is_match(s) || self.1.is_match(s)
+    }
+}
+
+impl<T: FlagsMarker, A: Query<T>, B: Query<T>, C: Query<T>> Query<T> for (A, B, C) {
+    type Iter<'t> = std::iter::Chain<std::iter::Chain<A::Iter<'t>, B::Iter<'t>>, C::Iter<'t>> where Self: 't;
+    fn items<'t>(&'t self) -> Self::Iter<'t> {
+        self.0.items().chain(self.1.items()).chain(self.2.items())
+    }
+    fn matches<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        self.0.matches(set) || self.1.matches(set) || self.2.matches(set)
+    }
+    fn matches_all<S: Lookup<T> + ?Sized>(&self, set: &S) -> bool {
+        self.0.matches(set) && self.1.matches(set) && self.2.matches(set)
+    }
+    fn is_match(&self, s: &str) -> bool {
+        self.0.is_match(s) || self.1.is_match(s) || self.2.is_match(s)
+    }
 }
 
 impl<T: FlagsMarker> Query<T> for str {
+    const RAW: bool = true;
     type Iter<'t> = std::iter::Once<&'t str>;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         std::iter::once(self)
@@ -27,6 +234,7 @@ impl<T: FlagsMarker> Query<T> for str {
 }
 
 impl<T: FlagsMarker> Query<T> for &str {
+    const RAW: bool = true;
     type Iter<'t> = std::iter::Once<&'t str> where Self: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         std::iter::once(self)
@@ -34,6 +242,7 @@ impl<T: FlagsMarker> Query<T> for &str {
 }
 
 impl<T: FlagsMarker> Query<T> for &&str {
+    const RAW: bool = true;
     type Iter<'t> = std::iter::Once<&'t str> where Self: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         std::iter::once(self)
@@ -41,6 +250,7 @@ impl<T: FlagsMarker> Query<T> for &&str {
 }
 
 impl<T: FlagsMarker> Query<T> for String {
+    const RAW: bool = true;
     type Iter<'t> = std::iter::Once<&'t str>;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         std::iter::once(self.as_str())
@@ -48,6 +258,7 @@ impl<T: FlagsMarker> Query<T> for String {
 }
 
 impl<T: FlagsMarker> Query<T> for &String {
+    const RAW: bool = true;
     type Iter<'t> = std::iter::Once<&'t str> where Self: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         std::iter::once(self.as_str())
@@ -55,6 +266,7 @@ impl<T: FlagsMarker> Query<T> for &String {
 }
 
 impl<'a, T: FlagsMarker> Query<T> for Cow<'a, str> {
+    const RAW: bool = true;
     type Iter<'t> = std::iter::Once<&'t str> where Self: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         std::iter::once(self.as_ref())
@@ -62,6 +274,7 @@ impl<'a, T: FlagsMarker> Query<T> for Cow<'a, str> {
 }
 
 impl<'a, 'b, T: FlagsMarker> Query<T> for &'b Cow<'a, str> {
+    const RAW: bool = true;
     type Iter<'t> = std::iter::Once<&'t str> where Self: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         std::iter::once(self.as_ref())
@@ -70,6 +283,7 @@ impl<'a, 'b, T: FlagsMarker> Query<T> for &'b Cow<'a, str> {
 
 
 impl<T: FlagsMarker> Query<T> for EcoString {
+    const RAW: bool = true;
     type Iter<'t> = std::iter::Once<&'t str>;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         std::iter::once(self.as_str())
@@ -77,6 +291,7 @@ impl<T: FlagsMarker> Query<T> for EcoString {
 }
 
 impl<T: FlagsMarker> Query<T> for &EcoString {
+    const RAW: bool = true;
     type Iter<'t> = std::iter::Once<&'t str> where Self: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         std::iter::once(self.as_str())
@@ -88,6 +303,9 @@ impl<T: FlagsMarker> Query<T> for T {
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         std::iter::once(self.as_ref())
     }
+    fn mask(&self) -> Option<u128> {
+        bit::<T>(self)
+    }
 }
 
 impl<T: FlagsMarker> Query<T> for &T {
@@ -95,6 +313,9 @@ impl<T: FlagsMarker> Query<T> for &T {
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         std::iter::once(self.as_ref())
     }
+    fn mask(&self) -> Option<u128> {
+        bit::<T>(self)
+    }
 }
 
 impl<T: FlagsMarker> Query<T> for &&T {
@@ -102,6 +323,9 @@ impl<T: FlagsMarker> Query<T> for &&T {
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         std::iter::once(self.as_ref())
     }
+    fn mask(&self) -> Option<u128> {
+        bit::<T>(self)
+    }
 }
 
 impl<T: FlagsMarker> Query<T> for &&&T {
@@ -109,6 +333,9 @@ impl<T: FlagsMarker> Query<T> for &&&T {
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         std::iter::once(self.as_ref())
     }
+    fn mask(&self) -> Option<u128> {
+        bit::<T>(self)
+    }
 }
 
 
@@ -125,6 +352,7 @@ impl<'t, T, K> Iterator for AsRefStrIter<'t, T>
 }
 
 impl<T: FlagsMarker + PartialEq> Query<T> for &[&str] {
+    const RAW: bool = true;
     type Iter<'t> = AsRefStrIter<'t, std::slice::Iter<'t, &'t str>> where Self: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         AsRefStrIter(self.iter(), PhantomData)
@@ -132,11 +360,15 @@ impl<T: FlagsMarker + PartialEq> Query<T> for &[&str] {
 }
 
 
+#[allow(clippy::needless_lifetimes)]
 impl<'a, T: FlagsMarker + PartialEq> Query<T> for &'a [T] {
     type Iter<'t> = AsRefStrIter<'t, std::slice::Iter<'t, T>> where Self: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         AsRefStrIter(self.iter(), PhantomData)
     }
+    fn mask(&self) -> Option<u128> {
+        self.iter().try_fold(0, |acc, x| Some(acc | bit(x)?))
+    }
 }
 
 impl<T: FlagsMarker + PartialEq> Query<T> for Vec<T> {
@@ -144,39 +376,72 @@ impl<T: FlagsMarker + PartialEq> Query<T> for Vec<T> {
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         AsRefStrIter(self.iter(), PhantomData)
     }
+    fn mask(&self) -> Option<u128> {
+        self.iter().try_fold(0, |acc, x| Some(acc | bit(x)?))
+    }
 }
 
+#[allow(clippy::needless_lifetimes)]
 impl<'a, T: FlagsMarker + PartialEq> Query<T> for &'a Vec<T> {
     type Iter<'t> = AsRefStrIter<'t, std::slice::Iter<'t, T>> where Self: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         AsRefStrIter(self.iter(), PhantomData)
     }
+    fn mask(&self) -> Option<u128> {
+        self.iter().try_fold(0, |acc, x| Some(acc | bit(x)?))
+    }
 }
 
-impl<T: FlagsMarker + PartialEq> Query<T> for Flags<T> {
-    type Iter<'t> = AsRefStrIter<'t, std::slice::Iter<'t, T>> where T: 't;
+impl<T: FlagsMarker + PartialEq, const S: char> Query<T> for Flags<T, S> {
+    type Iter<'t> = AsRefStrIter<'t, Iter<'t, T>> where T: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
-        AsRefStrIter(self.0.iter(), PhantomData)
+        AsRefStrIter(self.iter(), PhantomData)
+    }
+    fn mask(&self) -> Option<u128> {
+        self.custom.is_empty().then_some(self.known)
     }
 }
 
-impl<T: FlagsMarker + PartialEq> Query<T> for &Flags<T> {
-    type Iter<'t> = AsRefStrIter<'t, std::slice::Iter<'t, T>> where Self: 't;
+impl<T: FlagsMarker + PartialEq, const S: char> Query<T> for &Flags<T, S> {
+    type Iter<'t> = AsRefStrIter<'t, Iter<'t, T>> where Self: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
-        AsRefStrIter(self.0.iter(), PhantomData)
+        AsRefStrIter(self.iter(), PhantomData)
+    }
+    fn mask(&self) -> Option<u128> {
+        self.custom.is_empty().then_some(self.known)
     }
 }
 
-impl<T: FlagsMarker + PartialEq> Query<T> for &&Flags<T> {
-    type Iter<'t> = AsRefStrIter<'t, std::slice::Iter<'t, T>> where Self: 't;
+impl<T: FlagsMarker + PartialEq, const S: char> Query<T> for &&Flags<T, S> {
+    type Iter<'t> = AsRefStrIter<'t, Iter<'t, T>> where Self: 't;
+    fn items<'t>(&'t self) -> Self::Iter<'t> {
+        AsRefStrIter(self.iter(), PhantomData)
+    }
+    fn mask(&self) -> Option<u128> {
+        self.custom.is_empty().then_some(self.known)
+    }
+}
+
+impl<T: FlagsMarker + PartialEq, const S: char> Query<T> for &&&Flags<T, S> {
+    type Iter<'t> = AsRefStrIter<'t, Iter<'t, T>> where Self: 't;
+    fn items<'t>(&'t self) -> Self::Iter<'t> {
+        AsRefStrIter(self.iter(), PhantomData)
+    }
+    fn mask(&self) -> Option<u128> {
+        self.custom.is_empty().then_some(self.known)
+    }
+}
+
+impl<T: FlagsMarker + PartialEq, const S: char> Query<T> for LargeFlags<T, S> {
+    type Iter<'t> = AsRefStrIter<'t, std::collections::hash_set::Iter<'t, T>> where T: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         AsRefStrIter(self.0.iter(), PhantomData)
     }
 }
 
-impl<T: FlagsMarker + PartialEq> Query<T> for &&&Flags<T> {
-    type Iter<'t> = AsRefStrIter<'t, std::slice::Iter<'t, T>> where Self: 't;
+impl<T: FlagsMarker + PartialEq, const S: char> Query<T> for &LargeFlags<T, S> {
+    type Iter<'t> = AsRefStrIter<'t, std::collections::hash_set::Iter<'t, T>> where Self: 't;
     fn items<'t>(&'t self) -> Self::Iter<'t> {
         AsRefStrIter(self.0.iter(), PhantomData)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,99 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use crate::EcoString;
+
+/// A declared variant of a string enum.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct Variant {
+    pub name: &'static str,
+    pub index: usize,
+}
+
+/// Data of a string enum.
+///
+/// Declared variants point to a static [`Variant`],
+/// everything else is stored in an [`EcoString`].
+///
+/// Comparisons are done on the underlying string, after checking if
+/// two declared variants point to the same static data.
+#[doc(hidden)]
+#[derive(Clone)]
+pub enum Repr {
+    Known(&'static Variant),
+    Custom(EcoString),
+}
+
+impl Repr {
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Repr::Known(v) => v.name,
+            Repr::Custom(s) => s.as_str(),
+        }
+    }
+
+    /// Compares with a string, a declared variant is equal to its own static name
+    /// without reading the string.
+    #[inline]
+    pub fn eq_str(&self, other: &str) -> bool {
+        #[cfg(not(feature = "debug"))]
+        if let Repr::Known(v) = self {
+            if std::ptr::eq(v.name, other) {
+                return true;
+            }
+        }
+        crate::str_eq(self.as_str(), other)
+    }
+
+    #[inline]
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            Repr::Known(v) => Some(v.index),
+            Repr::Custom(_) => None,
+        }
+    }
+}
+
+impl Default for Repr {
+    fn default() -> Self {
+        Repr::Custom(EcoString::new())
+    }
+}
+
+impl std::fmt::Debug for Repr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl PartialEq for Repr {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Repr::Known(a), Repr::Known(b)) => std::ptr::eq(*a, *b) || a.name == b.name,
+            _ => self.as_str() == other.as_str(),
+        }
+    }
+}
+
+impl Eq for Repr {}
+
+impl Hash for Repr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl PartialOrd for Repr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Repr {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
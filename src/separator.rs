@@ -0,0 +1,174 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+
+/// Separates the items of [`Flags`](crate::Flags),
+/// used by [`Flags::parse_with`](crate::Flags::parse_with) and [`Flags::display_with`](crate::Flags::display_with).
+///
+/// Implemented for `char`, `&str` and [`Lenient`].
+/// Separators must not be empty or contain `\`, which is used for escaping.
+///
+/// ```
+/// # use strflags::*;
+/// # str_flags! { Animal: [ Dog, Cat, Whale ] }
+/// let flags = Flags::<Animal>::parse_with("dog, cat,whale", ", ").unwrap();
+/// assert_eq!(flags.display_with(" | ").to_string(), "dog | cat | whale");
+/// assert_eq!(Flags::<Animal>::parse_with("dog cat,\twhale", Lenient), Ok(flags));
+/// ```
+pub trait Separator {
+    /// Write the separator between two items.
+    fn write(&self, f: &mut dyn Write) -> std::fmt::Result;
+
+    /// Length in bytes of the separator at the start of `s`, if any.
+    fn strip(&self, s: &str) -> Option<usize>;
+
+    /// Returns `true` if the first character of `rest`, the remainder of an item,
+    /// must be escaped so the item is not split when parsed.
+    fn must_escape(&self, rest: &str) -> bool {
+        self.strip(rest).is_some()
+    }
+}
+
+impl Separator for char {
+    fn write(&self, f: &mut dyn Write) -> std::fmt::Result {
+        f.write_char(*self)
+    }
+
+    fn strip(&self, s: &str) -> Option<usize> {
+        s.starts_with(*self).then_some(self.len_utf8())
+    }
+}
+
+/// Written as is, parsing ignores whitespace around the separator,
+/// so `", "` also accepts `"dog,cat"`.
+impl Separator for &str {
+    fn write(&self, f: &mut dyn Write) -> std::fmt::Result {
+        f.write_str(self)
+    }
+
+    fn strip(&self, s: &str) -> Option<usize> {
+        let sep = significant(self);
+        (!sep.is_empty() && s.starts_with(sep)).then_some(sep.len())
+    }
+
+    fn must_escape(&self, rest: &str) -> bool {
+        self.strip(rest).is_some() || significant(self).starts_with(rest)
+    }
+}
+
+/// `sep` without surrounding whitespace, unless it is only whitespace.
+fn significant(sep: &str) -> &str {
+    match sep.trim() {
+        "" => sep,
+        trimmed => trimmed,
+    }
+}
+
+/// A [`Separator`] type with a single value, used as a type parameter
+/// like `strflags::serde::Sep::<Lenient>` with the `serde` feature.
+pub trait SeparatorMarker: Separator {
+    const SEPARATOR: Self;
+}
+
+/// Splits on any run of whitespace and commas, written as `", "`.
+///
+/// ```
+/// # use strflags::*;
+/// # str_flags! { Animal: [ Dog, Cat, Whale ] }
+/// let flags = Flags::<Animal>::parse_with(" dog,cat \n whale ,", Lenient).unwrap();
+/// assert_eq!(flags.display_with(Lenient).to_string(), "dog, cat, whale");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Lenient;
+
+impl Separator for Lenient {
+    fn write(&self, f: &mut dyn Write) -> std::fmt::Result {
+        f.write_str(", ")
+    }
+
+    fn strip(&self, s: &str) -> Option<usize> {
+        let len = s.len() - s.trim_start_matches(|c: char| c == ',' || c.is_whitespace()).len();
+        (len > 0).then_some(len)
+    }
+}
+
+impl SeparatorMarker for Lenient {
+    const SEPARATOR: Self = Lenient;
+}
+
+/// Write items separated by `sep`, with separators, `\`, and leading or trailing whitespace
+/// in items escaped by a `\`.
+pub(crate) fn write_separated<'t, T: AsRef<str> + 't>(
+    f: &mut impl Write,
+    sep: &impl Separator,
+    iter: impl Iterator<Item = &'t T>
+) -> std::fmt::Result {
+    for (i, item) in iter.enumerate() {
+        if i > 0 {
+            sep.write(f)?;
+        }
+        let item = item.as_ref();
+        let start = item.len() - item.trim_start().len();
+        let end = item.trim_end().len();
+        for (i, c) in item.char_indices() {
+            if c == '\\' || sep.must_escape(&item[i..]) || (c.is_whitespace() && (i < start || i >= end)) {
+                f.write_char('\\')?;
+            }
+            f.write_char(c)?;
+        }
+    }
+    Ok(())
+}
+
+/// Split `s` on separators not escaped by a `\`, into trimmed and unescaped items.
+///
+/// Empty input has no items.
+pub(crate) fn split_escaped<'t>(s: &'t str, sep: &'t impl Separator) -> impl Iterator<Item = Cow<'t, str>> {
+    let s = trim_escaped(s);
+    let mut rest = (!s.is_empty()).then_some(s);
+    std::iter::from_fn(move || {
+        let s = rest?;
+        let mut escaped = false;
+        let mut split = None;
+        for (i, c) in s.char_indices() {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if let Some(len) = sep.strip(&s[i..]) {
+                split = Some((i, len));
+                break;
+            }
+        }
+        let end = split.map_or(s.len(), |(i, _)| i);
+        rest = split.map(|(i, len)| &s[i + len..]);
+        Some(unescape(trim_escaped(&s[..end])))
+    })
+}
+
+/// Trim whitespace that is not escaped by a `\`.
+fn trim_escaped(s: &str) -> &str {
+    let s = s.trim_start();
+    let trimmed = s.trim_end();
+    let backslashes = trimmed.len() - trimmed.trim_end_matches('\\').len();
+    if backslashes % 2 == 1 {
+        let escaped = s[trimmed.len()..].chars().next().map_or(0, char::len_utf8);
+        &s[..trimmed.len() + escaped]
+    } else {
+        trimmed
+    }
+}
+
+fn unescape(s: &str) -> Cow<'_, str> {
+    if !s.contains('\\') {
+        return Cow::Borrowed(s);
+    }
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.push(chars.next().unwrap_or('\\')),
+            c => result.push(c),
+        }
+    }
+    Cow::Owned(result)
+}
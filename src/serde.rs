@@ -0,0 +1,211 @@
+//! Alternative serde formats for [`Flags`], for use with `#[serde(with = "...")]`.
+//!
+//! By default [`Flags`] is serialized as a string of items separated by `SEP`,
+//! e.g. `"dog|cat|giraffe"`.
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use ::serde::de::{Error, MapAccess, SeqAccess, Visitor};
+use ::serde::ser::{SerializeMap, SerializeSeq};
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Flags, LargeFlags, SeparatorMarker, StrEnum};
+
+impl<T: StrEnum + PartialEq, const SEP: char> Serialize for Flags<T, SEP> where T: Display {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de, T: StrEnum + PartialEq, const SEP: char> Deserialize<'de> for Flags<T, SEP> where T: FromStr, T::Err: Display {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        deserializer.deserialize_str(FlagsVisitor(PhantomData))
+    }
+}
+
+impl<T: Eq + Hash, const SEP: char> Serialize for LargeFlags<T, SEP> where T: StrEnum + Display {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de, T: Eq + Hash, const SEP: char> Deserialize<'de> for LargeFlags<T, SEP> where T: StrEnum + FromStr, T::Err: Display {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let s = Cow::<str>::deserialize(deserializer)?;
+        s.parse().map_err(|e| invalid::<D::Error, Self>(&s, e))
+    }
+}
+
+/// Error for input `s` of `V`, with the error of the item that failed to parse.
+fn invalid<E: Error, V>(s: &str, err: impl Display) -> E {
+    E::custom(format!("Invalid {}: \"{}\", {}", std::any::type_name::<V>(), s, err))
+}
+
+/// Accepts a separated string or a sequence of strings.
+struct FlagsVisitor<T, const SEP: char>(PhantomData<T>);
+
+impl<'de, T: StrEnum + PartialEq, const SEP: char> Visitor<'de> for FlagsVisitor<T, SEP> where T: FromStr, T::Err: Display {
+    type Value = Flags<T, SEP>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a string of items separated by '{}' or a sequence of strings", SEP)
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(|e| invalid::<E, Self::Value>(v, e))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut flags = Flags::EMPTY;
+        while let Some(item) = seq.next_element::<String>()? {
+            let item = item.trim();
+            if !item.is_empty() {
+                flags |= T::from_str(item).map_err(|e| invalid::<A::Error, Self::Value>(item, e))?;
+            }
+        }
+        Ok(flags)
+    }
+}
+
+/// Serialize [`Flags`] as a string of items separated by `P` instead of `SEP`,
+/// for separators that cannot be a `char`, see [`Separator`](crate::Separator).
+///
+/// `P` is a [`SeparatorMarker`], e.g. [`Lenient`](crate::Lenient) writes `"dog, cat"`
+/// and reads items separated by any run of whitespace and commas.
+///
+/// ```
+/// # use ::strflags::*;
+/// # use ::serde::{Serialize, Deserialize};
+/// # str_flags! { pub Animal: [ Dog, Cat ] }
+/// #[derive(Serialize, Deserialize)]
+/// struct Pet {
+///     #[serde(with = "strflags::serde::Sep::<Lenient>")]
+///     kinds: Flags<Animal>,
+/// }
+///
+/// let pet: Pet = serde_json::from_str(r#"{"kinds": "dog cat,"}"#).unwrap();
+/// assert_eq!(serde_json::to_string(&pet).unwrap(), r#"{"kinds":"dog, cat"}"#);
+/// ```
+pub struct Sep<P>(PhantomData<P>);
+
+impl<P: SeparatorMarker> Sep<P> {
+    pub fn serialize<S, T, const SEP: char>(flags: &Flags<T, SEP>, serializer: S)
+            -> Result<S::Ok, S::Error> where S: Serializer, T: StrEnum + PartialEq + Display {
+        serializer.collect_str(&flags.display_with(P::SEPARATOR))
+    }
+
+    pub fn deserialize<'de, D, T, const SEP: char>(deserializer: D)
+            -> Result<Flags<T, SEP>, D::Error> where D: Deserializer<'de>, T: StrEnum + PartialEq + FromStr, T::Err: Display {
+        let s = Cow::<str>::deserialize(deserializer)?;
+        Flags::parse_with(&s, P::SEPARATOR).map_err(|e| invalid::<D::Error, Flags<T, SEP>>(&s, e))
+    }
+}
+
+/// Iterate in the order used by `Display`.
+fn ordered<T: PartialEq + StrEnum, const SEP: char>(flags: &Flags<T, SEP>)
+        -> Box<dyn Iterator<Item = &T> + '_> {
+    if T::CANONICAL {
+        Box::new(flags.iter_canonical())
+    } else {
+        Box::new(flags.iter())
+    }
+}
+
+/// Serialize [`Flags`] as a sequence of strings, e.g. `["dog", "cat", "giraffe"]`.
+///
+/// Deserialization accepts either a sequence or a separated string.
+///
+/// ```
+/// # use ::strflags::*;
+/// # use ::serde::{Serialize, Deserialize};
+/// # str_flags! { pub Animal: [ Dog, Cat ] }
+/// #[derive(Serialize, Deserialize)]
+/// struct Pet {
+///     #[serde(with = "strflags::serde::seq")]
+///     kinds: Flags<Animal>,
+/// }
+/// ```
+pub mod seq {
+    use super::*;
+
+    pub fn serialize<S, T, const SEP: char>(flags: &Flags<T, SEP>, serializer: S)
+            -> Result<S::Ok, S::Error> where S: Serializer, T: PartialEq + StrEnum {
+        let mut seq = serializer.serialize_seq(Some(flags.len()))?;
+        for item in ordered(flags) {
+            seq.serialize_element(item.as_ref())?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D, T, const SEP: char>(deserializer: D)
+            -> Result<Flags<T, SEP>, D::Error> where D: Deserializer<'de>, T: StrEnum + PartialEq + FromStr, T::Err: Display {
+        deserializer.deserialize_any(FlagsVisitor(PhantomData))
+    }
+}
+
+/// Serialize [`Flags`] as a map of strings to booleans, e.g. `{"dog": true, "cat": false}`.
+///
+/// Every declared variant is written with an explicit `true` or `false`,
+/// custom values are written as `true`.
+/// Deserialization collects keys that are `true`.
+///
+/// ```
+/// # use ::strflags::*;
+/// # use ::serde::{Serialize, Deserialize};
+/// # str_flags! { pub Animal: [ Dog, Cat ] }
+/// #[derive(Serialize, Deserialize)]
+/// struct Pet {
+///     #[serde(with = "strflags::serde::bool_map")]
+///     kinds: Flags<Animal>,
+/// }
+/// ```
+pub mod bool_map {
+    use super::*;
+
+    pub fn serialize<S, T, const SEP: char>(flags: &Flags<T, SEP>, serializer: S)
+            -> Result<S::Ok, S::Error> where S: Serializer, T: PartialEq + StrEnum {
+        let custom = flags.iter().filter(|x| x.known_index().is_none()).count();
+        let mut map = serializer.serialize_map(Some(T::VARIANTS.len() + custom))?;
+        let known = flags.known_bits();
+        let wide: Vec<usize> = flags.iter().filter_map(T::known_index).filter(|i| *i >= 128).collect();
+        for (i, variant) in T::VARIANTS.iter().enumerate() {
+            let present = match 1u128.checked_shl(i as u32) {
+                Some(bit) => known & bit != 0,
+                None => wide.contains(&i),
+            };
+            map.serialize_entry(variant.as_ref(), &present)?;
+        }
+        for item in ordered(flags).filter(|x| x.known_index().is_none()) {
+            map.serialize_entry(item.as_ref(), &true)?;
+        }
+        map.end()
+    }
+
+    pub fn deserialize<'de, D, T, const SEP: char>(deserializer: D)
+            -> Result<Flags<T, SEP>, D::Error> where D: Deserializer<'de>, T: StrEnum + PartialEq + FromStr, T::Err: Display {
+        deserializer.deserialize_map(BoolMapVisitor(PhantomData))
+    }
+
+    struct BoolMapVisitor<T, const SEP: char>(PhantomData<T>);
+
+    impl<'de, T: StrEnum + PartialEq, const SEP: char> Visitor<'de> for BoolMapVisitor<T, SEP> where T: FromStr, T::Err: Display {
+        type Value = Flags<T, SEP>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a map of strings to booleans")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut flags = Flags::EMPTY;
+            while let Some((key, value)) = map.next_entry::<String, bool>()? {
+                if value {
+                    flags |= T::from_str(&key).map_err(|e| invalid::<A::Error, Self::Value>(&key, e))?;
+                }
+            }
+            Ok(flags)
+        }
+    }
+}
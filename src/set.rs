@@ -1,98 +1,543 @@
-use std::{fmt::Display, str::FromStr, marker::PhantomData};
-use crate::{query::AsRefStrIter, Query};
+use std::{borrow::Cow, cmp::Ordering, fmt::Display, str::FromStr, marker::PhantomData};
+use std::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+use crate::separator::{split_escaped, write_separated};
+use crate::{query::AsRefStrIter, ParseFlagsError, Pattern, Query, Separator};
 use smallvec::SmallVec;
 
+/// Implemented by all types generated by [`str_enum`](crate::str_enum).
+pub trait StrEnum: AsRef<str> + PartialEq<str> + Clone + Sized + 'static {
+    /// Every declared variant, in declaration order.
+    const VARIANTS: &'static [Self];
+
+    /// If `true`, [`Flags`] of this type are displayed and serialized
+    /// in canonical order, set with `#[canonical]`.
+    const CANONICAL: bool = false;
+
+    /// For each declared variant, bitmask of the variants it implies,
+    /// declared with `Admin: [Read, Write]`. Empty if there is no hierarchy.
+    ///
+    /// Bit `i` is `VARIANTS[i]`, implication is transitive.
+    const IMPLIES: &'static [u128] = &[];
+
+    /// Converts `s` to the canonical case and resolves declared variants and aliases,
+    /// same as the generated `new`.
+    fn new(s: &str) -> Self;
+
+    /// Converts `s` to the canonical case, without resolving variants or aliases.
+    ///
+    /// Returns `s` unchanged by default.
+    fn normalize(s: &str) -> Cow<'_, str> {
+        Cow::Borrowed(s)
+    }
+
+    /// Returns the position of this variant in `VARIANTS`, if declared.
+    fn known_index(&self) -> Option<usize>;
+}
+
 /// Marker for implementors of [`str_flags`](crate::str_flags).
-pub trait FlagsMarker: AsRef<str> + PartialEq<str> {}
+pub trait FlagsMarker: StrEnum {}
 
-/// A set of string-enums
+/// A set of string-enums.
+///
+/// Declared variants are stored in a bitmask, so membership of them is a single
+/// integer operation, and unions and intersections combine the bitmasks.
+/// Custom values, and variants past the first 128, are kept in a side list.
+///
+/// Items are iterated in insertion order, which set operations update
+/// in a single pass over the items.
 #[derive(Clone)]
-pub struct Flags<T: PartialEq, const SEP: char='|'>(pub(crate) SmallVec<[T; 2]>);
+pub struct Flags<T, const SEP: char='|'> {
+    pub(crate) known: u128,
+    pub(crate) custom: SmallVec<[T; 2]>,
+    /// Insertion order, the bit index of a declared variant in `known`,
+    /// or [`CUSTOM`] for the next item of `custom`.
+    order: SmallVec<[u8; 8]>,
+}
+
+/// Number of declared variants stored in the bitmask.
+const BITS: usize = u128::BITS as usize;
 
-impl<T: PartialEq, const S: char> Flags<T, S> {
-    pub const EMPTY: Self = Self(SmallVec::new_const());
+/// Entry of `Flags::order` for an item in the side list.
+const CUSTOM: u8 = u8::MAX;
+
+/// Bit indices set in `bits`, in ascending order.
+fn indices(mut bits: u128) -> impl Iterator<Item = u8> {
+    std::iter::from_fn(move || {
+        if bits == 0 {
+            return None;
+        }
+        let index = bits.trailing_zeros() as u8;
+        bits &= bits - 1;
+        Some(index)
+    })
+}
+
+/// Bit of a declared variant, `None` for values stored in the side list.
+#[inline]
+pub(crate) fn bit<T: StrEnum>(item: &T) -> Option<u128> {
+    match item.known_index() {
+        Some(index) if index < BITS => Some(1 << index),
+        _ => None,
+    }
+}
+
+impl<T: StrEnum + PartialEq, const S: char> Flags<T, S> {
+    pub const EMPTY: Self = Self { known: 0, custom: SmallVec::new_const(), order: SmallVec::new_const() };
 
     #[inline(always)]
     pub fn new(value: T) -> Self {
-        let mut vec = SmallVec::new_const();
-        vec.push(value);
-        Self(vec)
+        let mut result = Self::EMPTY;
+        result.insert(value);
+        result
     }
 
     #[inline(always)]
     pub fn pair(value1: T, value2: T) -> Self {
-        if value1 == value2{
-            Self::new(value1)
-        } else {
-            Self(SmallVec::from_const([value1, value2]))
+        let mut result = Self::new(value1);
+        result.insert(value2);
+        result
+    }
+
+    /// Returns a set containing every declared variant.
+    #[inline]
+    pub fn all() -> Self {
+        let len = T::VARIANTS.len();
+        let known = if len >= BITS { u128::MAX } else { (1 << len) - 1 };
+        Self {
+            known,
+            custom: T::VARIANTS.iter().skip(BITS).cloned().collect(),
+            order: indices(known).chain((BITS..len).map(|_| CUSTOM)).collect(),
         }
     }
 
+    /// Returns a set of the declared variants in `bits`,
+    /// where bit `i` is `VARIANTS[i]`, bits of undeclared variants are ignored.
+    #[inline]
+    pub fn from_known_bits(bits: u128) -> Self {
+        let known = bits & Self::all().known;
+        Self { known, custom: SmallVec::new_const(), order: indices(known).collect() }
+    }
+
+    /// Returns a bitmask of the first 128 declared variants in this set,
+    /// where bit `i` is `VARIANTS[i]`.
+    #[inline(always)]
+    pub fn known_bits(&self) -> u128 {
+        self.known
+    }
+
     #[inline(always)]
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.known.count_ones() as usize + self.custom.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.known == 0 && self.custom.is_empty()
     }
 
 
     #[inline(always)]
     pub fn is_some(&self) -> bool {
-        !self.0.is_empty()
+        !self.is_empty()
     }
 
     #[inline(always)]
     pub fn is_none(&self) -> bool {
-        self.0.is_empty()
+        self.is_empty()
     }
 
+    /// Returns `true` if any item of `t` is in the set.
+    ///
+    /// Use [`All`](crate::All), [`Any`](crate::Any) and [`Not`](crate::Not)
+    /// to combine queries.
     #[inline]
     pub fn contains(&self, t: impl Query<T>) -> bool where T: FlagsMarker{
-        for i in t.items() {
-            if self.0.iter().any(|x| x == i) {
-                return true;
-            }
+        t.matches(self)
+    }
+
+    /// Returns `true` if every item of `t` is in the set.
+    #[inline]
+    pub fn contains_all(&self, t: impl Query<T>) -> bool where T: FlagsMarker{
+        t.matches_all(self)
+    }
+
+    /// Returns `true` if no item of `t` is in the set.
+    #[inline]
+    pub fn contains_none(&self, t: impl Query<T>) -> bool where T: FlagsMarker{
+        !t.matches(self)
+    }
+
+    /// Returns `true` if every item of the set is matched by `t`.
+    pub fn is_subset(&self, t: impl Query<T>) -> bool where T: FlagsMarker{
+        match t.mask() {
+            Some(mask) => self.custom.is_empty() && self.known & !mask == 0,
+            None => self.iter().all(|x| t.is_match(x.as_ref())),
         }
-        false
+    }
+
+    /// Returns `true` if any item, or variant implied by an item, matches `pattern`,
+    /// same as `contains(pattern)`.
+    #[inline]
+    pub fn contains_matching(&self, pattern: &Pattern) -> bool where T: FlagsMarker {
+        pattern.matches(self)
+    }
+
+    /// Returns the items that match `pattern`,
+    /// including matching variants implied by an item, see [`Flags::expand`].
+    pub fn filter_matching(&self, pattern: &Pattern) -> Self {
+        let pattern = pattern.normalized::<T>();
+        self.iter_implied()
+            .filter(|x| pattern.is_match(x.as_ref()))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns `true` if every item of `t` is in the set, same as [`Flags::contains_all`].
+    #[inline]
+    pub fn is_superset(&self, t: impl Query<T>) -> bool where T: FlagsMarker{
+        t.matches_all(self)
+    }
+
+    /// Returns `true` if no item of `t` is in the set, same as [`Flags::contains_none`].
+    #[inline]
+    pub fn is_disjoint(&self, t: impl Query<T>) -> bool where T: FlagsMarker{
+        !t.matches(self)
     }
 
     #[inline(always)]
-    pub fn iter<'t>(&'t self) -> impl Iterator<Item = &'t T>{
-        self.0.iter()
+    pub fn iter<'t>(&'t self) -> Iter<'t, T> {
+        Iter { order: self.order.iter(), custom: self.custom.iter() }
+    }
+
+    /// Union of the variants implied by declared variants in `known`.
+    fn implied_by(known: u128) -> u128 {
+        let mut result = 0;
+        if !T::IMPLIES.is_empty() {
+            let mut bits = known;
+            while bits != 0 {
+                result |= T::IMPLIES[bits.trailing_zeros() as usize];
+                bits &= bits - 1;
+            }
+        }
+        result
+    }
+
+    /// Bitmask of the declared variants in the set and the variants they imply.
+    ///
+    /// This is what queries like [`Flags::contains`] are evaluated against.
+    #[inline]
+    pub fn implied_bits(&self) -> u128 {
+        self.known | Self::implied_by(self.known)
+    }
+
+    /// Iterate over the items, followed by the variants they imply.
+    #[inline]
+    pub(crate) fn iter_implied(&self) -> impl Iterator<Item = &T> {
+        let implied = Self::implied_by(self.known) & !self.known;
+        self.iter().chain(indices(implied).map(|index| &T::VARIANTS[index as usize]))
+    }
+
+    /// Returns a set with every variant implied by the items of this set added.
+    ///
+    /// ```
+    /// # use strflags::*;
+    /// str_flags! {
+    ///     Permission: [
+    ///         Admin: [Write, Delete],
+    ///         Write: [Read],
+    ///         Read,
+    ///         Delete,
+    ///     ]
+    /// }
+    ///
+    /// let flags = Flags::new(Permission::Admin);
+    /// assert!(flags.contains(Permission::Read));
+    /// assert_eq!(flags.expand(), Permission::Admin | Permission::Write | Permission::Read | Permission::Delete);
+    /// assert_eq!((Permission::Admin | Permission::Read).compact(), flags);
+    /// ```
+    ///
+    /// Implied variants are added after the existing items.
+    pub fn expand(&self) -> Self {
+        let mut result = self.clone();
+        let implied = Self::implied_by(self.known) & !self.known;
+        result.known |= implied;
+        result.order.extend(indices(implied));
+        result
+    }
+
+    /// Returns a set with every item implied by another item of this set removed.
+    pub fn compact(&self) -> Self {
+        let mut result = self.clone();
+        let implied = Self::implied_by(self.known);
+        result.retain(|x| !matches!(bit(x), Some(bit) if implied & bit != 0));
+        result
+    }
+
+    /// Adds a value, returns `false` if it was already present.
+    pub(crate) fn insert(&mut self, value: T) -> bool {
+        match bit(&value) {
+            Some(bit) if self.known & bit != 0 => false,
+            Some(bit) => {
+                self.known |= bit;
+                self.order.push(bit.trailing_zeros() as u8);
+                true
+            }
+            None if self.custom.contains(&value) => false,
+            None => {
+                self.custom.push(value);
+                self.order.push(CUSTOM);
+                true
+            }
+        }
+    }
+
+    /// Removes a value, returns `false` if it was not present.
+    pub(crate) fn remove(&mut self, value: &T) -> bool {
+        let (entry, nth) = match bit(value) {
+            Some(bit) if self.known & bit == 0 => return false,
+            Some(bit) => {
+                self.known &= !bit;
+                (bit.trailing_zeros() as u8, 0)
+            }
+            None => match self.custom.iter().position(|x| x == value) {
+                Some(index) => {
+                    self.custom.remove(index);
+                    (CUSTOM, index)
+                }
+                None => return false,
+            }
+        };
+        let position = self.order.iter()
+            .enumerate()
+            .filter(|(_, x)| **x == entry)
+            .nth(nth)
+            .map(|(i, _)| i)
+            .expect("insertion order out of sync with items");
+        self.order.remove(position);
+        true
+    }
+
+    /// Adds the items of `rhs` missing from this set, after the existing items.
+    pub(crate) fn union(&mut self, rhs: Self) {
+        let added = rhs.known & !self.known;
+        self.known |= rhs.known;
+        if added == 0 && rhs.custom.is_empty() {
+            return;
+        }
+        let mut custom = rhs.custom.into_iter();
+        for entry in rhs.order {
+            match entry {
+                CUSTOM => {
+                    let item = custom.next().expect("insertion order out of sync with items");
+                    if !self.custom.contains(&item) {
+                        self.custom.push(item);
+                        self.order.push(CUSTOM);
+                    }
+                }
+                index if added & (1 << index) != 0 => self.order.push(index),
+                _ => (),
+            }
+        }
+    }
+
+    /// Keeps the items also in `rhs`.
+    pub(crate) fn intersect(&mut self, rhs: &Self) {
+        let known = self.known;
+        self.known &= rhs.known;
+        self.sync(known, |x| rhs.custom.contains(x));
+    }
+
+    /// Removes the items in `rhs`.
+    pub(crate) fn difference(&mut self, rhs: &Self) {
+        let known = self.known;
+        self.known &= !rhs.known;
+        self.sync(known, |x| !rhs.custom.contains(x));
+    }
+
+    /// Removes entries of `order` for declared variants no longer in `known`,
+    /// and custom values for which `f` returns `false`.
+    ///
+    /// Skipped if there are no custom values and `known` is still `before`.
+    fn sync(&mut self, before: u128, mut f: impl FnMut(&T) -> bool) {
+        if self.custom.is_empty() && self.known == before {
+            return;
+        }
+        let known = self.known;
+        let mut custom = std::mem::take(&mut self.custom).into_iter();
+        let kept = &mut self.custom;
+        self.order.retain(|entry| match *entry {
+            CUSTOM => {
+                let item = custom.next().expect("insertion order out of sync with items");
+                let keep = f(&item);
+                if keep {
+                    kept.push(item);
+                }
+                keep
+            }
+            index => known & (1 << index) != 0,
+        });
+    }
+
+    /// Keeps the items for which `f` returns `true`, preserving their order.
+    pub(crate) fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        let mut custom = std::mem::take(&mut self.custom).into_iter();
+        let known = &mut self.known;
+        let kept = &mut self.custom;
+        self.order.retain(|entry| match *entry {
+            CUSTOM => {
+                let item = custom.next().expect("insertion order out of sync with items");
+                let keep = f(&item);
+                if keep {
+                    kept.push(item);
+                }
+                keep
+            }
+            index => {
+                let keep = f(&T::VARIANTS[index as usize]);
+                if !keep {
+                    *known &= !(1 << index);
+                }
+                keep
+            }
+        });
+    }
+
+    /// Iterate in canonical order,
+    /// declared variants in declaration order, then custom values sorted lexicographically.
+    pub fn iter_canonical(&self) -> impl Iterator<Item = &T> {
+        let mut items: SmallVec<[&T; 8]> = self.iter().collect();
+        items.sort_by(|a, b| match (a.known_index(), b.known_index()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => a.as_ref().cmp(b.as_ref()),
+        });
+        items.into_iter()
+    }
+
+    /// Format in canonical order, regardless of [`StrEnum::CANONICAL`].
+    ///
+    /// Equal sets always produce the same string.
+    pub fn to_canonical_string(&self) -> String where T: Display {
+        let mut result = String::new();
+        write_separated(&mut result, &S, self.iter_canonical())
+            .expect("a Display implementation returned an error unexpectedly");
+        result
+    }
+}
+
+/// Iterator over the items of [`Flags`], in insertion order.
+pub struct Iter<'t, T> {
+    order: std::slice::Iter<'t, u8>,
+    custom: std::slice::Iter<'t, T>,
+}
+
+impl<'t, T: StrEnum> Iterator for Iter<'t, T> {
+    type Item = &'t T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match *self.order.next()? {
+            CUSTOM => self.custom.next(),
+            index => Some(&T::VARIANTS[index as usize]),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.order.size_hint()
+    }
+}
+
+/// Owning iterator over the items of [`Flags`], in insertion order.
+pub struct IntoIter<T> {
+    order: smallvec::IntoIter<[u8; 8]>,
+    custom: smallvec::IntoIter<[T; 2]>,
+}
+
+impl<T: StrEnum> Iterator for IntoIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.order.next()? {
+            CUSTOM => self.custom.next(),
+            index => Some(T::VARIANTS[index as usize].clone()),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.order.size_hint()
     }
 }
 
-impl<T: PartialEq, const S: char> Default for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> Default for Flags<T, S> {
     fn default() -> Self {
         Self::EMPTY
     }
 }
 
-impl<T: PartialEq, const S: char> PartialEq for Flags<T, S> where T: PartialEq{
+/// Set equality, order and duplicates are ignored.
+impl<T: StrEnum + PartialEq, const S: char> PartialEq for Flags<T, S> {
     fn eq(&self, other: &Self) -> bool {
-        self.len() == other.len() ||
-            self.iter().all(|x| other.iter().any(|y| x == y))
+        self.known == other.known &&
+            self.custom.iter().all(|x| other.custom.contains(x)) &&
+            other.custom.iter().all(|x| self.custom.contains(x))
+    }
+}
+
+impl<T: StrEnum + Eq, const S: char> Eq for Flags<T, S> {}
+
+/// Order independent hash, consistent with [`PartialEq`].
+impl<T: StrEnum + PartialEq, const S: char> Hash for Flags<T, S> where T: Hash {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut sum = 0u64;
+        for item in self.custom.iter() {
+            let mut hasher = DefaultHasher::new();
+            item.hash(&mut hasher);
+            sum = sum.wrapping_add(hasher.finish());
+        }
+        state.write_u128(self.known);
+        state.write_u64(sum);
     }
 }
 
-impl<T: PartialEq, const S: char> IntoIterator for Flags<T, S> {
+impl<T: StrEnum + PartialEq, const S: char> FromIterator<T> for Flags<T, S> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut result = Self::EMPTY;
+        result.extend(iter);
+        result
+    }
+}
+
+impl<T: StrEnum + PartialEq, const S: char> Extend<T> for Flags<T, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<T: StrEnum, const S: char> IntoIterator for Flags<T, S> {
     type Item = T;
-    type IntoIter = smallvec::IntoIter<[T; 2]>;
+    type IntoIter = IntoIter<T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        IntoIter { order: self.order.into_iter(), custom: self.custom.into_iter() }
     }
 }
 
-impl<'t, T: PartialEq, const S: char> IntoIterator for &'t Flags<T, S> {
+impl<'t, T: StrEnum, const S: char> IntoIterator for &'t Flags<T, S> {
     type Item = &'t T;
-    type IntoIter = std::slice::Iter<'t, T>;
+    type IntoIter = Iter<'t, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.iter()
+        Iter { order: self.order.iter(), custom: self.custom.iter() }
     }
 }
 
 
-impl<T: PartialEq, const S: char> std::fmt::Debug for Flags<T, S> where T: AsRef<str>{
+impl<T: StrEnum + PartialEq, const S: char> std::fmt::Debug for Flags<T, S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut set = f.debug_set();
         set.entries(AsRefStrIter(self.iter(), PhantomData));
@@ -101,50 +546,69 @@ impl<T: PartialEq, const S: char> std::fmt::Debug for Flags<T, S> where T: AsRef
     }
 }
 
-impl<T: PartialEq, const S: char> Display for Flags<T, S> where T: Display {
+/// Items separated by `S`, in canonical order if [`StrEnum::CANONICAL`],
+/// otherwise in iteration order.
+///
+/// `S`, `\`, and leading or trailing whitespace in items are escaped with a `\`,
+/// so every item round-trips through [`FromStr`].
+impl<T: StrEnum + PartialEq, const S: char> Display for Flags<T, S> where T: Display {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut iter = self.iter();
-        if let Some(item) = iter.next() {
-            item.fmt(f)?;
-        }
-        for item in iter {
-            S.fmt(f)?;
-            item.fmt(f)?;
-        }
-        Ok(())
+        DisplayWith { flags: self, sep: S }.fmt(f)
     }
 }
 
 
-impl<T: PartialEq, const S: char> FromStr for Flags<T, S> where T: FromStr {
-    type Err = T::Err;
+impl<T: StrEnum + PartialEq, const S: char> Flags<T, S> {
+    /// Like [`FromStr`], but rejects empty items like `"dog||cat"`.
+    pub fn parse_strict(s: &str) -> Result<Self, ParseFlagsError<T::Err>> where T: FromStr {
+        split_escaped(s, &S).enumerate().try_fold(Self::EMPTY, |acc, (i, item)| {
+            if item.is_empty() {
+                return Err(ParseFlagsError::EmptyItem(i));
+            }
+            Ok(acc | T::from_str(&item).map_err(ParseFlagsError::Item)?)
+        })
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let arr: Result<_, _> = s.split(S).map(T::from_str).collect();
-        Ok(Self(arr?))
+    /// Like [`FromStr`], but with items separated by `sep` instead of `S`.
+    pub fn parse_with(s: &str, sep: impl Separator) -> Result<Self, T::Err> where T: FromStr {
+        split_escaped(s, &sep)
+            .filter(|item| !item.is_empty())
+            .try_fold(Self::EMPTY, |acc, item| Ok(acc | T::from_str(&item)?))
+    }
+
+    /// Like [`Display`], but with items separated by `sep` instead of `S`.
+    pub fn display_with<'t, P: Separator + 't>(&'t self, sep: P) -> impl Display + 't where T: Display {
+        DisplayWith { flags: self, sep }
     }
 }
 
-#[cfg(feature="serde")]
-const _: () = {
-    use ::serde::{Serialize, Deserialize};
+struct DisplayWith<'t, T: StrEnum + PartialEq, P, const S: char> {
+    flags: &'t Flags<T, S>,
+    sep: P,
+}
 
-    impl<T: PartialEq, const SEP: char> Serialize for Flags<T, SEP> where T: Display {
-        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
-            self.to_string().serialize(serializer)
+impl<T: StrEnum + PartialEq, P: Separator, const S: char> Display for DisplayWith<'_, T, P, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if T::CANONICAL {
+            write_separated(f, &self.sep, self.flags.iter_canonical())
+        } else {
+            write_separated(f, &self.sep, self.flags.iter())
         }
     }
+}
 
-    impl<'de, T: PartialEq, const SEP: char> Deserialize<'de> for Flags<T, SEP> where T: FromStr {
-        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: serde::Deserializer<'de> {
-            let s = <std::borrow::Cow<str>>::deserialize(deserializer)?;
-            let list: Result<_, _> = s.as_ref().split(SEP).map(|x| T::from_str(x)).collect();
-            match list{
-                Ok(list) => Ok(Self(list)),
-                Err(_) => Err(serde::de::Error::custom(
-                    format!("Invalid {}: \"{}\".", ::std::any::type_name::<Self>(), s))
-                )
-            }
-        }
+/// Parses a list of items separated by `S`.
+///
+/// Items are trimmed, empty items and duplicates are ignored.
+/// An empty string is parsed as [`Flags::EMPTY`].
+///
+/// `\` escapes the next character, so `S` can appear in an item as `\S`.
+impl<T: StrEnum + PartialEq, const S: char> FromStr for Flags<T, S> where T: FromStr {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        split_escaped(s, &S)
+            .filter(|item| !item.is_empty())
+            .try_fold(Self::EMPTY, |acc, item| Ok(acc | T::from_str(&item)?))
     }
-};
+}
@@ -0,0 +1,594 @@
+//! Procedural macro backend for [`strflags`](https://docs.rs/strflags).
+//!
+//! This crate is an implementation detail,
+//! use `str_enum!` and `str_flags!` from `strflags` instead.
+use convert_case::{Boundary, Case, Casing};
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{bracketed, parse_macro_input, Attribute, Ident, LitChar, LitStr, Token, Visibility};
+
+struct Input {
+    attrs: Vec<Attribute>,
+    options: Options,
+    vis: Visibility,
+    name: Ident,
+    variants: Punctuated<Variant, Token![,]>,
+}
+
+/// Options specified as attributes on the type.
+#[derive(Default)]
+struct Options {
+    strict: bool,
+    canonical: bool,
+    case: Normalize,
+    bitflags: Option<Ident>,
+    separator: Option<LitChar>,
+}
+
+/// Canonical case of a type, selected by `#[case = "..."]`.
+#[derive(Clone, Copy, Default)]
+enum Normalize {
+    #[default]
+    Flat,
+    Snake,
+    Kebab,
+    ScreamingSnake,
+    Verbatim,
+}
+
+impl Normalize {
+    fn parse(lit: &LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "flatcase" => Normalize::Flat,
+            "snake_case" => Normalize::Snake,
+            "kebab-case" => Normalize::Kebab,
+            "SCREAMING_SNAKE_CASE" => Normalize::ScreamingSnake,
+            "verbatim" => Normalize::Verbatim,
+            _ => return Err(syn::Error::new(lit.span(), concat!(
+                "expected one of \"flatcase\", \"snake_case\", \"kebab-case\", ",
+                "\"SCREAMING_SNAKE_CASE\" or \"verbatim\"",
+            ))),
+        })
+    }
+
+    fn case(self) -> Option<Case> {
+        match self {
+            Normalize::Flat => Some(Case::Flat),
+            Normalize::Snake => Some(Case::Snake),
+            Normalize::Kebab => Some(Case::Kebab),
+            Normalize::ScreamingSnake => Some(Case::ScreamingSnake),
+            Normalize::Verbatim => None,
+        }
+    }
+
+    /// Convert a string at compile time.
+    fn convert(self, s: &str) -> String {
+        match self.case() {
+            Some(case) => s.to_case(case),
+            None => s.to_owned(),
+        }
+    }
+
+    /// Expression that converts `s` at runtime, as a `Cow<str>`.
+    fn runtime(self) -> TokenStream2 {
+        let case = match self {
+            Normalize::Flat => quote!(Flat),
+            Normalize::Snake => quote!(Snake),
+            Normalize::Kebab => quote!(Kebab),
+            Normalize::ScreamingSnake => quote!(ScreamingSnake),
+            Normalize::Verbatim => return quote!(::strflags::normalize(s, None)),
+        };
+        quote!(::strflags::normalize(s, Some(::strflags::convert_case::Case::#case)))
+    }
+}
+
+impl Options {
+    /// Remove our own attributes from `attrs`.
+    fn extract(attrs: &mut Vec<Attribute>) -> syn::Result<Self> {
+        let mut options = Options::default();
+        let mut result = Ok(());
+        attrs.retain(|attr| {
+            let parsed = if attr.path().is_ident("strict") {
+                attr.meta.require_path_only().map(|_| options.strict = true)
+            } else if attr.path().is_ident("canonical") {
+                attr.meta.require_path_only().map(|_| options.canonical = true)
+            } else if attr.path().is_ident("case") {
+                attr.meta.require_name_value()
+                    .and_then(|meta| syn::parse2(meta.value.to_token_stream()))
+                    .and_then(|lit| Normalize::parse(&lit))
+                    .map(|case| options.case = case)
+            } else if attr.path().is_ident("separator") {
+                attr.parse_args::<LitChar>()
+                    .and_then(|sep| match sep.value() {
+                        '\\' => Err(syn::Error::new(sep.span(), "`\\` is reserved for escaping")),
+                        _ => Ok(sep),
+                    })
+                    .map(|sep| options.separator = Some(sep))
+            } else if attr.path().is_ident("bitflags") {
+                attr.parse_args::<Ident>().map(|ident| options.bitflags = Some(ident))
+            } else {
+                return true;
+            };
+            if let Err(err) = parsed {
+                match &mut result {
+                    Ok(()) => result = Err(err),
+                    Err(e) => syn::Error::combine(e, err),
+                }
+            }
+            false
+        });
+        result.map(|_| options)
+    }
+}
+
+struct Variant {
+    attrs: Vec<Attribute>,
+    ident: Ident,
+    label: Option<LitStr>,
+    value: Option<LitStr>,
+    aliases: Vec<LitStr>,
+    children: Vec<Ident>,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let options = Options::extract(&mut attrs)?;
+        let vis = input.parse()?;
+        let name = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let content;
+        bracketed!(content in input);
+        let variants = content.parse_terminated(Variant::parse, Token![,])?;
+        Ok(Self { attrs, options, vis, name, variants })
+    }
+}
+
+impl Parse for Variant {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let mut label = None;
+        let mut result = Ok(());
+        attrs.retain(|attr| {
+            if !attr.path().is_ident("label") {
+                return true;
+            }
+            match attr.parse_args::<LitStr>() {
+                Ok(lit) => label = Some(lit),
+                Err(err) => result = Err(err),
+            }
+            false
+        });
+        result?;
+        let ident = input.parse()?;
+        let mut value = None;
+        let mut aliases = Vec::new();
+        if input.parse::<Option<Token![=]>>()?.is_some() {
+            value = input.parse()?;
+            if value.is_none() || input.peek(syn::token::Bracket) {
+                let content;
+                bracketed!(content in input);
+                aliases = content.parse_terminated(<LitStr as Parse>::parse, Token![,])?
+                    .into_iter()
+                    .collect();
+            }
+        }
+        let mut children = Vec::new();
+        if input.parse::<Option<Token![:]>>()?.is_some() {
+            let content;
+            bracketed!(content in input);
+            children = content.parse_terminated(Ident::parse, Token![,])?
+                .into_iter()
+                .collect();
+        }
+        Ok(Self { attrs, ident, label, value, aliases, children })
+    }
+}
+
+impl Variant {
+    /// The canonical string, stored as is.
+    fn value(&self, case: Normalize) -> String {
+        match &self.value {
+            Some(value) => value.value(),
+            None => case.convert(&self.ident.to_string()),
+        }
+    }
+
+    /// Human readable name, defaults to the identifier in Title Case.
+    fn label(&self) -> String {
+        match &self.label {
+            Some(label) => label.value(),
+            None => self.ident.to_string().to_case(Case::Title),
+        }
+    }
+
+    /// Every normalized string that resolves to this variant,
+    /// paired with the span to report conflicts on.
+    fn keys(&self, case: Normalize) -> impl Iterator<Item = (String, Span)> + '_ {
+        let span = match &self.value {
+            Some(value) => value.span(),
+            None => self.ident.span(),
+        };
+        std::iter::once((case.convert(&self.value(case)), span))
+            .chain(self.aliases.iter().map(move |alias| (case.convert(&alias.value()), alias.span())))
+    }
+}
+
+/// Implementation of `strflags::str_enum!`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __str_enum(tokens: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(tokens as Input);
+    if let Some(bits) = &input.options.bitflags {
+        return syn::Error::new(bits.span(), "`#[bitflags]` is only supported by `str_flags!`")
+            .into_compile_error()
+            .into();
+    }
+    if let Some(sep) = &input.options.separator {
+        return syn::Error::new(sep.span(), "`#[separator]` is only supported by `str_flags!`")
+            .into_compile_error()
+            .into();
+    }
+    match str_enum(&input) {
+        Ok(stream) => stream.into(),
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+/// Implementation of `strflags::str_flags!`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __str_flags(tokens: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(tokens as Input);
+    match str_enum(&input).and_then(|stream| Ok((stream, str_flags(&input)?))) {
+        Ok((stream, flags)) => quote!(#stream #flags).into(),
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+fn validate(input: &Input) -> syn::Result<()> {
+    let mut result: syn::Result<()> = Ok(());
+    let mut seen: Vec<(String, &Ident)> = Vec::new();
+    for variant in &input.variants {
+        for (key, span) in variant.keys(input.options.case) {
+            if let Some((_, prev)) = seen.iter().find(|(k, _)| k == &key) {
+                let err = syn::Error::new(span, format!(
+                    "\"{}\" already refers to variant `{}`",
+                    key, prev,
+                ));
+                match &mut result {
+                    Ok(()) => result = Err(err),
+                    Err(e) => e.combine(err),
+                }
+            } else {
+                seen.push((key, &variant.ident));
+            }
+        }
+    }
+    result
+}
+
+/// Bitmask of the variants implied by each variant, transitively,
+/// `None` if there is no hierarchy.
+fn implies(input: &Input) -> syn::Result<Option<Vec<u128>>> {
+    if input.variants.iter().all(|variant| variant.children.is_empty()) {
+        return Ok(None);
+    }
+    let index_of = |ident: &Ident| -> syn::Result<usize> {
+        match input.variants.iter().position(|variant| &variant.ident == ident) {
+            Some(index) if index < 128 => Ok(index),
+            Some(_) => Err(syn::Error::new(ident.span(), "only the first 128 variants can be part of a hierarchy")),
+            None => Err(syn::Error::new(ident.span(), format!("no variant named `{}`", ident))),
+        }
+    };
+    let mut masks = vec![0u128; input.variants.len()];
+    for (index, variant) in input.variants.iter().enumerate() {
+        if !variant.children.is_empty() {
+            index_of(&variant.ident)?;
+        }
+        for child in &variant.children {
+            masks[index] |= 1 << index_of(child)?;
+        }
+    }
+    loop {
+        let mut changed = false;
+        for index in 0..masks.len() {
+            let mut mask = masks[index];
+            for (child, implied) in masks.iter().enumerate().take(128) {
+                if mask & (1 << child) != 0 {
+                    mask |= implied;
+                }
+            }
+            changed |= mask != masks[index];
+            masks[index] = mask;
+        }
+        if !changed {
+            break;
+        }
+    }
+    for (index, variant) in input.variants.iter().enumerate() {
+        if index < 128 && masks[index] & (1 << index) != 0 {
+            return Err(syn::Error::new(variant.ident.span(), format!(
+                "variant `{}` implies itself", variant.ident,
+            )));
+        }
+    }
+    Ok(Some(masks))
+}
+
+fn str_enum(input: &Input) -> syn::Result<TokenStream2> {
+    validate(input)?;
+    let implies = implies(input)?.map(|masks| {
+        let masks = masks.into_iter().map(proc_macro2::Literal::u128_unsuffixed);
+        quote!(const IMPLIES: &'static [u128] = &[#(#masks),*];)
+    });
+    let Input { attrs, options, vis, name, .. } = input;
+    let consts = input.variants.iter().enumerate().map(|(index, variant)| {
+        let attrs = &variant.attrs;
+        let ident = &variant.ident;
+        let value = variant.value(options.case);
+        quote! {
+            #(#attrs)*
+            pub const #ident: Self = Self(::strflags::Repr::Known(
+                &::strflags::Variant { name: #value, index: #index }
+            ));
+        }
+    });
+    let idents = input.variants.iter().map(|variant| &variant.ident);
+    let names = input.variants.iter().map(|variant| variant.value(options.case));
+    let keys = input.variants.iter().map(|variant| options.case.convert(&variant.value(options.case)));
+    let normalize = options.case.runtime();
+    let canonical = options.canonical;
+    let labels = input.variants.iter().map(Variant::label);
+    let arms = input.variants.iter().map(|variant| {
+        let ident = &variant.ident;
+        let keys = variant.keys(options.case).map(|(key, _)| key);
+        quote!(#(#keys)|* => Self::#ident,)
+    });
+    let from_str = if options.strict {
+        quote! {
+            type Err = ::strflags::UnknownVariant;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::try_new(s)
+            }
+        }
+    } else {
+        quote! {
+            type Err = ::std::convert::Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(Self::new(s))
+            }
+        }
+    };
+    Ok(quote! {
+        #[derive(Debug, Clone, Eq, Hash)]
+        #(#attrs)*
+        #vis struct #name(::strflags::Repr);
+
+        const _: () = {
+
+            #[allow(non_upper_case_globals)]
+            impl #name {
+                #(#consts)*
+
+                /// Every declared variant, in declaration order.
+                pub const VARIANTS: &'static [Self] = &[#(Self::#idents),*];
+
+                /// String value of every declared variant, in declaration order.
+                pub const NAMES: &'static [&'static str] = &[#(#names),*];
+
+                #[inline]
+                pub fn new(s: &str) -> Self {
+                    let s = #normalize;
+                    match &*s {
+                        #(#arms)*
+                        _ => Self(::strflags::Repr::Custom((&*s).into())),
+                    }
+                }
+
+                /// Like `new`, but rejects strings that are not declared variants.
+                #[inline]
+                pub fn try_new(s: &str) -> Result<Self, ::strflags::UnknownVariant> {
+                    let result = Self::new(s);
+                    if result.is_known() {
+                        Ok(result)
+                    } else {
+                        let suggestion = Self::suggest(s)
+                            .and_then(|x| x.known_index())
+                            .map(|i| Self::NAMES[i]);
+                        Err(::strflags::UnknownVariant::new(s, Self::NAMES, suggestion))
+                    }
+                }
+
+                /// Returns the declared variant most similar to `s`, if any.
+                pub fn suggest(s: &str) -> Option<Self> {
+                    let s = Self::new(s);
+                    if s.is_known() {
+                        return Some(s);
+                    }
+                    const KEYS: &[&str] = &[#(#keys),*];
+                    ::strflags::closest(s.0.as_str(), KEYS)
+                        .map(|i| Self::VARIANTS[i].clone())
+                }
+
+                /// Returns a human readable name.
+                ///
+                /// Custom values are converted to Title Case.
+                pub fn label(&self) -> ::std::borrow::Cow<'static, str> {
+                    const LABELS: &[&str] = &[#(#labels),*];
+                    match self.known_index() {
+                        Some(i) => ::std::borrow::Cow::Borrowed(LABELS[i]),
+                        None => {
+                            use ::strflags::convert_case::{Casing, Case::Title};
+                            ::std::borrow::Cow::Owned(self.0.as_str().to_case(Title))
+                        }
+                    }
+                }
+
+                /// Returns `true` if this is one of the declared variants.
+                #[inline]
+                pub fn is_known(&self) -> bool {
+                    self.0.index().is_some()
+                }
+
+                /// Returns `true` if this is not one of the declared variants.
+                #[inline]
+                pub fn is_custom(&self) -> bool {
+                    self.0.index().is_none()
+                }
+
+                /// Returns the position of this variant in `VARIANTS`, if declared.
+                #[inline]
+                pub fn known_index(&self) -> Option<usize> {
+                    self.0.index()
+                }
+            }
+
+            impl ::strflags::StrEnum for #name {
+                const VARIANTS: &'static [Self] = #name::VARIANTS;
+                const CANONICAL: bool = #canonical;
+                #implies
+
+                fn new(s: &str) -> Self {
+                    #name::new(s)
+                }
+
+                fn normalize(s: &str) -> ::std::borrow::Cow<'_, str> {
+                    #normalize
+                }
+
+                fn known_index(&self) -> Option<usize> {
+                    #name::known_index(self)
+                }
+            }
+
+            impl ::std::fmt::Display for #name {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    f.write_str(self.0.as_str())
+                }
+            }
+
+            impl ::std::str::FromStr for #name {
+                #from_str
+            }
+
+            #[allow(clippy::from_over_into)]
+            impl<const S: char> Into<::strflags::Flags<Self, S>> for #name {
+                fn into(self) -> ::strflags::Flags<Self, S> {
+                    ::strflags::Flags::new(self)
+                }
+            }
+
+            impl ::std::borrow::Borrow<str> for #name {
+                fn borrow(&self) -> &str {
+                    self.0.as_str()
+                }
+            }
+
+            impl AsRef<str> for #name {
+                fn as_ref(&self) -> &str {
+                    self.0.as_str()
+                }
+            }
+
+            impl PartialEq<str> for #name {
+                fn eq(&self, other: &str) -> bool {
+                    self.0.as_str() == other
+                }
+            }
+
+            impl<T: AsRef<str>> ::std::cmp::PartialEq<T> for #name {
+                fn eq(&self, other: &T) -> bool {
+                    self.0.eq_str(other.as_ref())
+                }
+            }
+
+        };
+
+        ::strflags::impl_serde!(#name);
+    })
+}
+
+/// Arguments of `strflags::impl_bitflags!`, if `#[bitflags]` is specified.
+fn bitflags(input: &Input, sep: &TokenStream2) -> syn::Result<TokenStream2> {
+    let Some(bits) = &input.options.bitflags else {
+        return Ok(TokenStream2::new());
+    };
+    let ty = match input.variants.len() {
+        0..=8 => quote!(u8),
+        9..=16 => quote!(u16),
+        17..=32 => quote!(u32),
+        33..=64 => quote!(u64),
+        65..=128 => quote!(u128),
+        _ => return Err(syn::Error::new(bits.span(), "`#[bitflags]` supports at most 128 variants")),
+    };
+    let Input { vis, name, .. } = input;
+    let flags = input.variants.iter().enumerate().map(|(index, variant)| {
+        let flag = variant.ident.to_string()
+            .with_boundaries(&[Boundary::LowerUpper, Boundary::Acronym])
+            .to_case(Case::ScreamingSnake);
+        let flag = Ident::new(&flag, variant.ident.span());
+        let index = proc_macro2::Literal::usize_unsuffixed(index);
+        quote!(#flag = 1 << #index)
+    });
+    Ok(quote! {
+        ::strflags::impl_bitflags!(#vis #bits: #ty, #name, #sep, [#(#flags),*]);
+    })
+}
+
+fn str_flags(input: &Input) -> syn::Result<TokenStream2> {
+    let Input { vis, name, .. } = input;
+    let (sep, alias) = match &input.options.separator {
+        Some(sep) => {
+            let alias = Ident::new(&format!("{}Flags", name), name.span());
+            let doc = format!(" [`Flags`](::strflags::Flags) of [`{}`] separated by `{}`.", name, sep.value());
+            (sep.to_token_stream(), quote! {
+                #[doc = #doc]
+                #vis type #alias = ::strflags::Flags<#name, #sep>;
+            })
+        }
+        None => (quote!('|'), TokenStream2::new()),
+    };
+    let bitflags = bitflags(input, &sep)?;
+    Ok(quote! {
+        const _: () = {
+            impl ::strflags::FlagsMarker for #name {}
+
+            impl ::std::ops::BitOr for #name {
+                type Output = ::strflags::Flags<Self, #sep>;
+                fn bitor(self, rhs: Self) -> Self::Output {
+                    ::strflags::Flags::pair(self, rhs)
+                }
+            }
+
+            impl ::std::ops::BitOr<::strflags::Flags<Self, #sep>> for #name {
+                type Output = ::strflags::Flags<Self, #sep>;
+                fn bitor(self, rhs: ::strflags::Flags<Self, #sep>) -> Self::Output {
+                    rhs | self
+                }
+            }
+
+            impl ::std::ops::BitAnd<::strflags::Flags<Self, #sep>> for #name {
+                type Output = ::strflags::Flags<Self, #sep>;
+                fn bitand(self, rhs: ::strflags::Flags<Self, #sep>) -> Self::Output {
+                    rhs & self
+                }
+            }
+
+            impl ::std::ops::BitXor<::strflags::Flags<Self, #sep>> for #name {
+                type Output = ::strflags::Flags<Self, #sep>;
+                fn bitxor(self, rhs: ::strflags::Flags<Self, #sep>) -> Self::Output {
+                    rhs ^ self
+                }
+            }
+        };
+
+        #alias
+        #bitflags
+    })
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "bitflags")]
+use strflags::*;
+
+str_flags! {
+    #[bitflags(AnimalBits)]
+    pub Animal: [
+        Dog,
+        Cat,
+        GiantPanda,
+    ]
+}
+
+str_flags! {
+    #[bitflags(WideBits)]
+    Wide: [
+        W0, W1, W2, W3, W4, W5, W6, W7, HttpV2,
+    ]
+}
+
+#[test]
+fn bitflags() {
+    assert_eq!(AnimalBits::DOG.bits(), 1);
+    assert_eq!(AnimalBits::GIANT_PANDA.bits(), 4);
+    assert_eq!(AnimalBits::all().bits(), 7);
+    assert_eq!(WideBits::HTTP_V2.bits(), 256u16);
+
+    let bits = AnimalBits::CAT | AnimalBits::GIANT_PANDA;
+    let flags = Animal::Cat | Animal::GiantPanda;
+    assert_eq!(Flags::from(bits), flags);
+    assert_eq!(AnimalBits::try_from(flags.clone()), Ok(bits));
+    assert_eq!(Flags::from(AnimalBits::empty()), Flags::EMPTY);
+
+    let flags = flags | Animal::new("GiantPandas");
+    let err = AnimalBits::try_from(flags.clone()).unwrap_err();
+    assert_eq!(err.input(), "giantpandas");
+    assert_eq!(err.suggestion(), Some("giantpanda"));
+    assert_eq!(AnimalBits::from_flags_truncate(&flags), bits);
+}
@@ -0,0 +1,202 @@
+#![cfg(feature = "serde")]
+use strflags::*;
+
+str_flags! {
+    Animal: [
+        Dog,
+        Cat,
+        Giraffe,
+    ]
+}
+
+str_enum! {
+    #[strict]
+    Method: [
+        Get,
+        Post,
+    ]
+}
+
+#[test]
+fn enum_serde() {
+    assert_eq!(serde_json::to_string(&Animal::Dog).unwrap(), r#""dog""#);
+    assert_eq!(serde_json::from_str::<Animal>(r#""Cat""#).unwrap(), Animal::Cat);
+    assert_eq!(serde_json::from_str::<Animal>(r#""Shark""#).unwrap(), "shark");
+}
+
+#[test]
+fn strict_serde() {
+    assert_eq!(serde_json::from_str::<Method>(r#""GET""#).unwrap(), Method::Get);
+    let err = serde_json::from_str::<Method>(r#""Put""#).unwrap_err();
+    assert!(err.to_string().contains(r#"unknown variant "Put""#));
+
+    let err = serde_json::from_str::<Flags<Method>>(r#""get|posts""#).unwrap_err();
+    assert!(err.to_string().contains(r#""get|posts", unknown variant "posts", did you mean "post"?"#));
+
+    #[derive(Debug, ::serde::Deserialize)]
+    struct Route {
+        #[serde(with = "strflags::serde::seq")]
+        #[allow(dead_code)]
+        methods: Flags<Method>,
+    }
+    let err = serde_json::from_str::<Route>(r#"{"methods":["get","put"]}"#).unwrap_err();
+    assert!(err.to_string().contains(r#""put", unknown variant "put", expected one of "get", "post""#));
+}
+
+#[test]
+fn flags_serde() {
+    let flags = Animal::Dog | Animal::Cat;
+    assert_eq!(serde_json::to_string(&flags).unwrap(), r#""dog|cat""#);
+    assert_eq!(serde_json::from_str::<Flags<Animal>>(r#""dog|cat""#).unwrap(), flags);
+    assert_eq!(serde_json::from_str::<Flags<Animal>>(r#""dog | cat|dog""#).unwrap(), flags);
+    assert!(serde_json::from_str::<Flags<Animal>>(r#""""#).unwrap().is_empty());
+}
+
+str_enum! {
+    #[case = "snake_case"]
+    Snake: [
+        DarkBlue,
+    ]
+}
+
+#[test]
+fn case_serde() {
+    assert_eq!(serde_json::to_string(&Snake::DarkBlue).unwrap(), r#""dark_blue""#);
+    assert_eq!(serde_json::from_str::<Snake>(r#""dark_blue""#).unwrap(), Snake::DarkBlue);
+    let custom = serde_json::from_str::<Snake>(r#""light_blue""#).unwrap();
+    assert_eq!(serde_json::to_string(&custom).unwrap(), r#""light_blue""#);
+}
+
+str_flags! {
+    #[canonical]
+    Sorted: [
+        Zebra,
+        Ant,
+    ]
+}
+
+#[test]
+fn canonical_serde() {
+    let flags = Sorted::new("Bee") | Sorted::Ant | Sorted::Zebra;
+    assert_eq!(serde_json::to_string(&flags).unwrap(), r#""zebra|ant|bee""#);
+}
+
+#[derive(Debug, ::serde::Serialize, ::serde::Deserialize)]
+struct Pet {
+    #[serde(with = "strflags::serde::seq")]
+    kinds: Flags<Animal>,
+}
+
+#[test]
+fn seq_serde() {
+    let pet = Pet { kinds: Animal::Dog | Animal::new("Shark") };
+    assert_eq!(serde_json::to_string(&pet).unwrap(), r#"{"kinds":["dog","shark"]}"#);
+
+    let pet: Pet = serde_json::from_str(r#"{"kinds":["Dog", "cat", "dog"]}"#).unwrap();
+    assert_eq!(pet.kinds, Animal::Dog | Animal::Cat);
+    let pet: Pet = serde_json::from_str(r#"{"kinds":"dog|cat"}"#).unwrap();
+    assert_eq!(pet.kinds, Animal::Dog | Animal::Cat);
+    let pet: Pet = serde_json::from_str(r#"{"kinds":[]}"#).unwrap();
+    assert!(pet.kinds.is_empty());
+    assert!(serde_json::from_str::<Pet>(r#"{"kinds":1}"#).is_err());
+}
+
+#[derive(Debug, ::serde::Serialize, ::serde::Deserialize)]
+struct Zoo {
+    #[serde(with = "strflags::serde::bool_map")]
+    kinds: Flags<Animal>,
+}
+
+#[test]
+fn bool_map_serde() {
+    let zoo = Zoo { kinds: Animal::new("Shark") | Animal::Cat };
+    assert_eq!(
+        serde_json::to_string(&zoo).unwrap(),
+        r#"{"kinds":{"dog":false,"cat":true,"giraffe":false,"shark":true}}"#
+    );
+
+    let zoo: Zoo = serde_json::from_str(r#"{"kinds":{"dog":true,"cat":false,"Whale":true}}"#).unwrap();
+    assert_eq!(zoo.kinds, Animal::Dog | Animal::new("whale"));
+    let zoo: Zoo = serde_json::from_str(r#"{"kinds":{}}"#).unwrap();
+    assert!(zoo.kinds.is_empty());
+    assert!(serde_json::from_str::<Zoo>(r#"{"kinds":{"dog":1}}"#).is_err());
+}
+
+str_flags! {
+    Wide: [
+        W0, W1, W2, W3, W4, W5, W6, W7, W8, W9,
+        W10, W11, W12, W13, W14, W15, W16, W17, W18, W19,
+        W20, W21, W22, W23, W24, W25, W26, W27, W28, W29,
+        W30, W31, W32, W33, W34, W35, W36, W37, W38, W39,
+        W40, W41, W42, W43, W44, W45, W46, W47, W48, W49,
+        W50, W51, W52, W53, W54, W55, W56, W57, W58, W59,
+        W60, W61, W62, W63, W64, W65, W66, W67, W68, W69,
+        W70, W71, W72, W73, W74, W75, W76, W77, W78, W79,
+        W80, W81, W82, W83, W84, W85, W86, W87, W88, W89,
+        W90, W91, W92, W93, W94, W95, W96, W97, W98, W99,
+        W100, W101, W102, W103, W104, W105, W106, W107, W108, W109,
+        W110, W111, W112, W113, W114, W115, W116, W117, W118, W119,
+        W120, W121, W122, W123, W124, W125, W126, W127, W128, W129,
+    ]
+}
+
+#[derive(Debug, ::serde::Serialize)]
+struct WideZoo {
+    #[serde(with = "strflags::serde::bool_map")]
+    kinds: Flags<Wide>,
+}
+
+#[test]
+fn bool_map_wide() {
+    let zoo = WideZoo { kinds: Wide::W0 | Wide::W127 | Wide::W129 };
+    let value = serde_json::to_value(&zoo).unwrap();
+    let kinds = value["kinds"].as_object().unwrap();
+    assert_eq!(kinds.len(), 130);
+    assert_eq!(kinds.values().filter(|x| x == &&serde_json::Value::Bool(true)).count(), 3);
+    assert_eq!(kinds["w127"], true);
+    assert_eq!(kinds["w128"], false);
+    assert_eq!(kinds["w129"], true);
+}
+
+struct Spaced;
+
+impl Separator for Spaced {
+    fn write(&self, f: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        f.write_str(" | ")
+    }
+
+    fn strip(&self, s: &str) -> Option<usize> {
+        "|".strip(s)
+    }
+}
+
+impl SeparatorMarker for Spaced {
+    const SEPARATOR: Self = Spaced;
+}
+
+#[derive(Debug, ::serde::Serialize, ::serde::Deserialize)]
+struct Tagged {
+    #[serde(with = "strflags::serde::Sep::<Lenient>")]
+    lenient: Flags<Animal>,
+    #[serde(with = "strflags::serde::Sep::<Spaced>")]
+    spaced: Flags<Animal>,
+}
+
+#[test]
+fn separator_serde() {
+    let tagged: Tagged = serde_json::from_str(r#"{"lenient":" dog\tcat,, shark ","spaced":"cat|dog "}"#).unwrap();
+    assert_eq!(tagged.lenient, Animal::Dog | Animal::Cat | Animal::new("Shark"));
+    assert_eq!(tagged.spaced, Animal::Cat | Animal::Dog);
+    assert_eq!(
+        serde_json::to_string(&tagged).unwrap(),
+        r#"{"lenient":"dog, cat, shark","spaced":"cat | dog"}"#
+    );
+    assert!(serde_json::from_str::<Tagged>(r#"{"lenient":["dog"],"spaced":""}"#).is_err());
+}
+
+#[test]
+fn large_serde() {
+    let flags = LargeFlags::from(Animal::new("Shark") | Animal::Cat);
+    assert_eq!(serde_json::to_string(&flags).unwrap(), r#""cat|shark""#);
+    assert_eq!(serde_json::from_str::<LargeFlags<Animal>>(r#""shark|cat""#).unwrap(), flags);
+}
@@ -1,4 +1,6 @@
 
+use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
 use strflags::*;
 
 str_flags! {
@@ -19,7 +21,6 @@ str_flags! {
 
 str_enum! {
     VeryLarge : [
-        // This is fine as long as we don't use it
         NNopjejqiewjqvckqnvkoqpvjqpcqkc
     ]
 }
@@ -32,6 +33,156 @@ fn stringify() {
 }
 
 #[test]
+fn static_variants() {
+    assert_eq!(VeryLarge::NNopjejqiewjqvckqnvkoqpvjqpcqkc, "nnopjejqiewjqvckqnvkoqpvjqpcqkc");
+    assert_eq!(VeryLarge::new("NNopjejqiewjqvckqnvkoqpvjqpcqkc"), VeryLarge::NNopjejqiewjqvckqnvkoqpvjqpcqkc);
+    assert_eq!(Animal::new("Dog"), Animal::Dog);
+    assert_eq!(Animal::Dog, Animal::VARIANTS[0]);
+    assert_ne!(Animal::Dog, Animal::Cat);
+    assert_eq!(Animal::Dog, String::from("dog"));
+    assert_eq!(Animal::Dog, Dialect::new("dog"));
+    assert_eq!(Animal::new("a very long custom animal name"), "averylongcustomanimalname");
+}
+
+#[test]
+fn variants() {
+    assert_eq!(Animal::VARIANTS.len(), 6);
+    assert_eq!(Animal::VARIANTS[1], Animal::Cat);
+    assert_eq!(Animal::NAMES, &["dog", "cat", "rabbit", "giraffe", "whale", "dolphin"]);
+    for (variant, name) in Animal::VARIANTS.iter().zip(Animal::NAMES) {
+        assert_eq!(variant, name);
+    }
+
+    let all = Flags::<Animal>::all();
+    assert_eq!(all.len(), 6);
+    assert!(Animal::VARIANTS.iter().all(|x| all.contains(x)));
+    assert!(!all.contains(Animal::new("Shark")));
+}
+
+#[test]
+fn known() {
+    assert!(Animal::Whale.is_known());
+    assert!(!Animal::Whale.is_custom());
+    assert_eq!(Animal::Whale.known_index(), Some(4));
+    assert_eq!(Animal::new("WHALE").known_index(), Some(4));
+
+    let shark = Animal::new("Shark");
+    assert!(!shark.is_known());
+    assert!(shark.is_custom());
+    assert_eq!(shark.known_index(), None);
+    assert!(Language::default().is_custom());
+}
+
+str_enum! {
+    #[strict]
+    Method: [
+        Get,
+        Post,
+    ]
+}
+
+str_enum! {
+    HttpMethod: [
+        Get = "GET",
+        Post = "POST" ["postmethod"],
+        MSearch = "M-SEARCH",
+        Options,
+    ]
+}
+
+#[test]
+fn explicit_values() {
+    assert_eq!(HttpMethod::Get, "GET");
+    assert_eq!(HttpMethod::Get.to_string(), "GET");
+    assert_eq!(HttpMethod::new("get"), HttpMethod::Get);
+    assert_eq!(HttpMethod::new("GET").to_string(), "GET");
+    assert_eq!(HttpMethod::new("post_method"), HttpMethod::Post);
+    assert_eq!(HttpMethod::new("M-SEARCH"), HttpMethod::MSearch);
+    assert_eq!(HttpMethod::new("OPTIONS"), HttpMethod::Options);
+    assert!(HttpMethod::new("msearch").is_known());
+    assert!(HttpMethod::new("Get").is_known());
+    assert_eq!(HttpMethod::NAMES, &["GET", "POST", "M-SEARCH", "options"]);
+    assert_eq!(HttpMethod::suggest("Postt"), Some(HttpMethod::Post));
+}
+
+str_enum! {
+    #[case = "snake_case"]
+    Snake: [
+        DarkBlue,
+        #[label("C++")]
+        CPlusPlus = ["c++"],
+        Html = "text/html",
+    ]
+}
+
+str_enum! {
+    #[case = "SCREAMING_SNAKE_CASE"]
+    Screaming: [
+        DarkBlue,
+    ]
+}
+
+str_enum! {
+    #[case = "verbatim"]
+    Verbatim: [
+        DarkBlue,
+    ]
+}
+
+#[test]
+fn case() {
+    assert_eq!(Snake::DarkBlue, "dark_blue");
+    assert_eq!(Snake::new("DarkBlue"), Snake::DarkBlue);
+    assert_eq!(Snake::new("dark-blue"), Snake::DarkBlue);
+    assert_eq!(Snake::new("c++"), Snake::CPlusPlus);
+    assert_eq!(Snake::new("TEXT/HTML"), Snake::Html);
+    assert_eq!(Snake::new("LightBlue").to_string(), "light_blue");
+    assert_eq!(Snake::NAMES, &["dark_blue", "c_plus_plus", "text/html"]);
+
+    assert_eq!(Screaming::new("dark blue").to_string(), "DARK_BLUE");
+
+    assert_eq!(Verbatim::DarkBlue, "DarkBlue");
+    assert_eq!(Verbatim::new("DarkBlue"), Verbatim::DarkBlue);
+    assert!(Verbatim::new("darkblue").is_custom());
+    assert_eq!(Verbatim::new("Dark Blue"), "Dark Blue");
+}
+
+#[test]
+fn label() {
+    assert_eq!(Snake::CPlusPlus.label(), "C++");
+    assert_eq!(Snake::DarkBlue.label(), "Dark Blue");
+    assert_eq!(Snake::Html.label(), "Html");
+    assert_eq!(Snake::new("light-blue").label(), "Light Blue");
+    assert_eq!(Animal::new("Sea Lion").label(), "Sealion");
+}
+
+#[test]
+fn strict() {
+    assert_eq!(Animal::try_new("Dog"), Ok(Animal::Dog));
+    let err = Animal::try_new("Shark").unwrap_err();
+    assert_eq!(err.input(), "Shark");
+    assert_eq!(err.expected(), Animal::NAMES);
+    assert_eq!("Shark".parse::<Animal>().unwrap(), "shark");
+
+    assert_eq!("post".parse(), Ok(Method::Post));
+    let err = "Put".parse::<Method>().unwrap_err();
+    assert_eq!(err.to_string(), r#"unknown variant "Put", expected one of "get", "post""#);
+    assert_eq!(Method::new("Put"), "put");
+}
+
+#[test]
+fn suggest() {
+    assert_eq!(Animal::suggest("Girafe"), Some(Animal::Giraffe));
+    assert_eq!(Animal::suggest("dolphin"), Some(Animal::Dolphin));
+    assert_eq!(Animal::suggest("Shark"), None);
+
+    let err = Animal::try_new("Dolphn").unwrap_err();
+    assert_eq!(err.suggestion(), Some("dolphin"));
+    assert_eq!(err.to_string(), r#"unknown variant "Dolphn", did you mean "dolphin"?"#);
+}
+
+#[test]
+#[allow(clippy::len_zero)]
 fn init_ops() {
     type F = Flags<Animal, '|'>;
 
@@ -144,6 +295,517 @@ fn sub() {
 }
 
 
+/// Calls `f` on every permutation of `items`.
+#[allow(clippy::manual_is_multiple_of)]
+fn permutations<T: Clone>(items: &mut [T], k: usize, f: &mut impl FnMut(&[T])) {
+    if k <= 1 {
+        return f(items);
+    }
+    permutations(items, k - 1, f);
+    for i in 0..k - 1 {
+        items.swap(if k % 2 == 0 { i } else { 0 }, k - 1);
+        permutations(items, k - 1, f);
+    }
+}
+
+#[test]
+fn equality() {
+    let hasher = RandomState::new();
+    let flags = |items: &[Animal]| items.iter().cloned().fold(Flags::<Animal>::EMPTY, |a, b| a | b);
+
+    let mut items = vec![Animal::Dog, Animal::Cat, Animal::Whale, Animal::new("Shark")];
+    let expected = flags(&items);
+    permutations(&mut items.clone(), 4, &mut |perm| {
+        let perm = flags(perm);
+        assert_eq!(perm, expected);
+        assert_eq!(hasher.hash_one(&perm), hasher.hash_one(&expected));
+    });
+
+    items.pop();
+    permutations(&mut items, 3, &mut |perm| {
+        assert_ne!(flags(perm), expected);
+        assert_ne!(expected, flags(perm));
+    });
+
+    assert_ne!(Animal::Dog | Animal::Cat, Animal::Whale | Animal::Rabbit);
+    assert_ne!(Flags::<Animal>::new(Animal::Dog), Flags::EMPTY);
+    assert_eq!(Flags::<Animal>::EMPTY, Flags::EMPTY);
+
+    let dup: Flags<Animal> = "dog|cat|dog".parse().unwrap();
+    let other: Flags<Animal> = "cat|dog".parse().unwrap();
+    assert_eq!(dup, other);
+    assert_eq!(hasher.hash_one(&dup), hasher.hash_one(&other));
+    assert_ne!(dup, "dog|whale|dog".parse().unwrap());
+
+    let mut map = HashMap::new();
+    map.insert(Animal::Dog | Animal::Cat, 1);
+    map.insert(Animal::Cat | Animal::Dog, 2);
+    map.insert(Animal::Cat | Animal::Whale, 3);
+    assert_eq!(map.len(), 2);
+    assert_eq!(map[&(Animal::Dog | Animal::Cat)], 2);
+}
+
+#[test]
+fn parse() {
+    type F = Flags<Animal>;
+    assert_eq!("".parse::<F>().unwrap(), F::EMPTY);
+    assert!("  ".parse::<F>().unwrap().is_empty());
+    assert_eq!("dog| cat ".parse::<F>().unwrap(), Animal::Dog | Animal::Cat);
+    assert!(" dog | cat".parse::<F>().unwrap().contains(Animal::Cat));
+    assert_eq!("dog|dog|cat|dog".parse::<F>().unwrap().len(), 2);
+    assert_eq!("dog||cat|".parse::<F>().unwrap().len(), 2);
+
+    assert_eq!(F::parse_strict(""), Ok(F::EMPTY));
+    assert_eq!(F::parse_strict("dog | cat|dog").unwrap().len(), 2);
+    assert_eq!(F::parse_strict("dog||cat"), Err(ParseFlagsError::EmptyItem(1)));
+    assert_eq!(F::parse_strict("dog| "), Err(ParseFlagsError::EmptyItem(1)));
+
+    type M = Flags<Method>;
+    assert!(M::parse_strict("get|post").is_ok());
+    assert!(matches!(M::parse_strict("get|put"), Err(ParseFlagsError::Item(_))));
+    assert!("get|put".parse::<M>().is_err());
+}
+
+str_flags! {
+    #[case = "verbatim"]
+    Raw: [
+        Plain,
+    ]
+}
+
+#[test]
+fn escaping() {
+    type F = Flags<Raw>;
+    let flags = Raw::Plain | Raw::new("a|b") | Raw::new(r"c:\d") | Raw::new(" padded ") | Raw::new("in side");
+    assert_eq!(flags.to_string(), r"Plain|a\|b|c:\\d|\ padded\ |in side");
+    assert_eq!(flags.to_string().parse::<F>().unwrap(), flags);
+    assert_eq!(F::parse_strict(&flags.to_string()).unwrap(), flags);
+    assert_eq!(flags.to_canonical_string().parse::<F>().unwrap(), flags);
+
+    assert_eq!(r" a\|b | c\\ ".parse::<F>().unwrap(), Raw::new("a|b") | Raw::new(r"c\"));
+    assert_eq!(r"\  |\|".parse::<F>().unwrap(), Raw::new(" ") | Raw::new("|"));
+    assert_eq!(r"tail\".parse::<F>().unwrap(), Flags::new(Raw::new(r"tail\")));
+
+    let large: LargeFlags<Raw> = flags.clone().into();
+    assert_eq!(large.to_string().parse::<LargeFlags<Raw>>().unwrap(), large);
+}
+
+str_flags! {
+    #[separator(';')]
+    #[case = "verbatim"]
+    pub Color: [
+        Red,
+        Green,
+    ]
+}
+
+#[test]
+fn separator() {
+    let colors: ColorFlags = Color::Red | Color::new("a|b") | Color::new("c;d");
+    assert_eq!(colors.to_string(), r"Red;a|b;c\;d");
+    assert_eq!(colors.to_string().parse::<ColorFlags>().unwrap(), colors);
+    assert_eq!("Red; Green".parse::<ColorFlags>().unwrap(), Color::Red | Color::Green);
+    assert_eq!(Color::Red | ColorFlags::new(Color::Green), Color::Green | Color::Red);
+    let into: ColorFlags = Color::Red.into();
+    assert_eq!(into, Flags::new(Color::Red));
+
+    let red: ColorFlags = Flags::new(Color::Red);
+    assert!(colors.contains(&red));
+    assert!(colors.contains_all(&red));
+    assert!(colors.is_superset(red.clone()));
+    assert!(red.is_subset(&colors));
+    assert!(!colors.is_subset(&red));
+    assert!(red.is_disjoint(Color::Green | Color::new("c;d")));
+    let large: LargeFlags<Color, ';'> = colors.clone().into();
+    assert!(colors.contains_all(&large));
+    assert!(large.contains_all(&colors));
+}
+
+#[test]
+fn separators() {
+    type F = Flags<Raw>;
+    let flags = Raw::Plain | Raw::new("a, b") | Raw::new("c,") | Raw::new("d e");
+    assert_eq!(flags.display_with(", ").to_string(), r"Plain, a\, b, c\,, d e");
+    assert_eq!(F::parse_with(&flags.display_with(", ").to_string(), ", ").unwrap(), flags);
+    assert_eq!(F::parse_with("Plain,d e , a\\,", ", ").unwrap(), Raw::Plain | Raw::new("d e") | Raw::new("a,"));
+
+    assert_eq!(flags.display_with(Lenient).to_string(), r"Plain, a\,\ b, c\,, d\ e");
+    assert_eq!(F::parse_with(&flags.display_with(Lenient).to_string(), Lenient).unwrap(), flags);
+    assert_eq!(F::parse_with("\tPlain  x,,y\n", Lenient).unwrap(), Raw::Plain | Raw::new("x") | Raw::new("y"));
+    assert_eq!(F::parse_with(" ,\n ", Lenient).unwrap(), F::EMPTY);
+
+    let pipes = Raw::new("x|") | Raw::new("|y") | Raw::new("z");
+    assert_eq!(pipes.display_with("||").to_string(), r"x\||||y||z");
+    assert_eq!(F::parse_with(&pipes.display_with("||").to_string(), "||").unwrap(), pipes);
+    assert_eq!(F::parse_with("a|b || c", " || ").unwrap(), Raw::new("a|b") | Raw::new("c"));
+
+    assert_eq!(flags.display_with('|').to_string(), flags.to_string());
+    assert_eq!(F::parse_with("Plain|x", '|').unwrap(), "Plain|x".parse::<F>().unwrap());
+}
+
+str_flags! {
+    #[canonical]
+    Sorted: [
+        Zebra,
+        Ant,
+    ]
+}
+
+#[test]
+fn canonical() {
+    let animals = Animal::new("Shark") | Animal::Whale | Animal::new("Eel") | Animal::Dog;
+    assert_eq!(animals.to_string(), "shark|whale|eel|dog");
+    assert_eq!(animals.to_canonical_string(), "dog|whale|eel|shark");
+    let reversed = Animal::Dog | Animal::new("Eel") | Animal::Whale | Animal::new("Shark");
+    assert_eq!(animals.to_canonical_string(), reversed.to_canonical_string());
+    assert_eq!(Flags::<Animal>::EMPTY.to_canonical_string(), "");
+
+    let sorted = Sorted::new("Bee") | Sorted::Ant | Sorted::new("Ape") | Sorted::Zebra;
+    assert_eq!(sorted.to_string(), "zebra|ant|ape|bee");
+    assert_eq!(sorted.iter().next(), Some(&Sorted::new("Bee")));
+}
+
+str_flags! {
+    Wide: [
+        W0, W1, W2, W3, W4, W5, W6, W7, W8, W9,
+        W10, W11, W12, W13, W14, W15, W16, W17, W18, W19,
+        W20, W21, W22, W23, W24, W25, W26, W27, W28, W29,
+        W30, W31, W32, W33, W34, W35, W36, W37, W38, W39,
+        W40, W41, W42, W43, W44, W45, W46, W47, W48, W49,
+        W50, W51, W52, W53, W54, W55, W56, W57, W58, W59,
+        W60, W61, W62, W63, W64, W65, W66, W67, W68, W69,
+        W70, W71, W72, W73, W74, W75, W76, W77, W78, W79,
+        W80, W81, W82, W83, W84, W85, W86, W87, W88, W89,
+        W90, W91, W92, W93, W94, W95, W96, W97, W98, W99,
+        W100, W101, W102, W103, W104, W105, W106, W107, W108, W109,
+        W110, W111, W112, W113, W114, W115, W116, W117, W118, W119,
+        W120, W121, W122, W123, W124, W125, W126, W127, W128, W129,
+    ]
+}
+#[test]
+fn bitset() {
+    let flags = Animal::Whale | Animal::new("Shark") | Animal::Dog;
+    assert_eq!(flags.len(), 3);
+    assert!(flags.contains(Animal::Dog));
+    assert!(flags.contains(&[Animal::Cat, Animal::Whale][..]));
+    assert!(!flags.contains(Animal::Cat | Animal::Rabbit));
+    assert!(flags.contains(Animal::Cat | Animal::new("Shark")));
+    assert!(flags.contains("shark"));
+    assert_eq!(flags.iter().map(|x| x.as_ref()).collect::<Vec<_>>(), ["whale", "shark", "dog"]);
+
+    assert_eq!(flags.clone() & (Animal::Dog | Animal::Cat), Flags::new(Animal::Dog));
+    assert_eq!(flags.clone() ^ (Animal::Dog | Animal::Cat), Animal::Cat | Animal::Whale | Animal::new("Shark"));
+    assert_eq!(flags.clone() - Animal::new("Shark"), Animal::Dog | Animal::Whale);
+    assert_eq!(flags.clone() & Animal::new("Shark"), Flags::new(Animal::new("Shark")));
+    assert_eq!(flags.into_iter().collect::<Flags<Animal>>(), Animal::new("Shark") | Animal::Whale | Animal::Dog);
+
+    let ordered = Animal::new("Shark") | Animal::Whale | Animal::new("Eel") | Animal::Dog;
+    assert_eq!((ordered.clone() - Animal::Whale).to_string(), "shark|eel|dog");
+    assert_eq!((ordered.clone() & (Animal::Dog | Animal::new("Shark"))).to_string(), "shark|dog");
+    assert_eq!((ordered.clone() ^ (Animal::Cat | Animal::new("Eel"))).to_string(), "shark|whale|dog|cat");
+    assert_eq!((ordered.clone() | Animal::Cat | Animal::Whale).to_string(), "shark|whale|eel|dog|cat");
+    assert_eq!((ordered.clone() | (Animal::Cat | Animal::new("Eel") | Animal::new("Ray") | Animal::Dog)).to_string(), "shark|whale|eel|dog|cat|ray");
+    assert_eq!((ordered.clone() - (Animal::new("Eel") | Animal::Whale)).to_string(), "shark|dog");
+    assert_eq!((ordered.clone() & (Animal::Dog | Animal::Whale)).to_string(), "whale|dog");
+    assert_eq!(ordered.into_iter().nth(3), Some(Animal::Dog));
+
+    let all = Flags::<Wide>::all();
+    assert_eq!(all.len(), 130);
+    assert!(all.contains(Wide::W129));
+    assert_eq!(all.iter().last(), Some(&Wide::W129));
+    let wide = Wide::W0 | Wide::W127 | Wide::W128 | Wide::W129;
+    assert_eq!(wide.clone() - Wide::W128, Wide::W129 | Wide::W127 | Wide::W0);
+    assert_eq!(wide.clone() & Wide::W129, Flags::new(Wide::W129));
+    assert_eq!(wide.to_string(), "w0|w127|w128|w129");
+}
+
+#[test]
+fn combinators() {
+    let flags = Animal::Dog | Animal::Cat | Animal::new("Shark");
+    assert!(flags.contains_all(Animal::Dog | Animal::Cat));
+    assert!(flags.contains_all(&[Animal::Dog, Animal::new("Shark")][..]));
+    assert!(!flags.contains_all(Animal::Dog | Animal::Whale));
+    assert!(flags.contains_all(Flags::<Animal>::EMPTY));
+    assert!(flags.contains_none(Animal::Whale | Animal::new("Eel")));
+    assert!(!flags.contains_none(Animal::Whale | Animal::new("Shark")));
+
+    assert!(flags.is_superset(&flags));
+    assert!(flags.is_subset(&flags));
+    assert!(flags.is_subset(flags.clone() | Animal::Whale));
+    assert!(!flags.is_subset(Animal::Dog | Animal::Cat));
+    assert!((Animal::Dog | Animal::Cat).is_subset(&flags));
+    assert!(flags.is_disjoint(Animal::Rabbit));
+    assert!(!flags.is_disjoint("shark"));
+
+    assert!(flags.contains(All(Animal::Dog | Animal::new("Shark"))));
+    assert!(!flags.contains(All(Animal::Dog | Animal::Whale)));
+    assert!(flags.contains(Any(Animal::Dog | Animal::Whale)));
+    assert!(flags.contains(Not(Animal::Whale)));
+    assert!(flags.contains(Not(All(Animal::Dog | Animal::Whale))));
+    assert!(!flags.contains(Not(Any(Animal::Dog | Animal::Whale))));
+    assert!(flags.contains((Animal::Whale, Animal::Cat)));
+    assert!(flags.contains(All((Animal::Dog, Not(Animal::Whale)))));
+    assert!(!flags.contains(All((Animal::Dog, Not(Animal::Cat), "shark"))));
+
+    let large = LargeFlags::from(flags);
+    assert!(large.contains(All(Animal::Dog | Animal::new("Shark"))));
+    assert!(!large.contains(All(Animal::Dog | Animal::Whale)));
+    assert!(large.contains(Not(Animal::Whale)));
+}
+
+#[test]
+fn query_case() {
+    use std::borrow::Cow;
+    use strflags::convert_case::Case;
+
+    let flags = Animal::Giraffe | Animal::new("Great White");
+    assert!(flags.contains("Giraffe"));
+    assert!(flags.contains(String::from("GIRAFFE")));
+    assert!(flags.contains("great white"));
+    assert!(flags.contains(Cow::Borrowed("GreatWhite")));
+    assert!(!flags.contains("Dog"));
+    assert!(flags.contains_all(&["giraffe", "Great-White"][..]));
+    assert!(flags.is_subset(&["Giraffe", "GreatWhite", "Dog"][..]));
+    assert!((Dialect::new("c++") | Dialect::Rust).contains_all(&["CXX", "Rust"][..]));
+
+    assert!(matches!(strflags::normalize("darkblue", Some(Case::Flat)), Cow::Borrowed(_)));
+    assert!(matches!(strflags::normalize("dark_blue", Some(Case::Snake)), Cow::Borrowed(_)));
+    assert!(matches!(strflags::normalize("DARK_BLUE", Some(Case::ScreamingSnake)), Cow::Borrowed(_)));
+    assert!(matches!(strflags::normalize("DarkBlue", None), Cow::Borrowed(_)));
+    assert_eq!(strflags::normalize("DarkBlue", Some(Case::Flat)), "darkblue");
+    assert_eq!(strflags::normalize("dark__blue", Some(Case::Snake)), "dark_blue");
+    assert_eq!(strflags::normalize("dark-blue", Some(Case::Kebab)), "dark-blue");
+}
+
+#[test]
+fn patterns() {
+    let flags = Animal::Dog | Animal::Dolphin | Animal::new("RegionEu") | Animal::new("RegionUs");
+    assert!(flags.contains(Pattern::prefix("region")));
+    let suffix = Pattern::suffix("phin");
+    assert!(flags.contains(&suffix));
+    assert!(flags.contains_matching(&suffix));
+    assert!(!flags.contains(Pattern::prefix("cat")));
+    assert!(flags.contains_matching(&Pattern::glob("do*")));
+    assert!(flags.contains_matching(&Pattern::glob("*e?")));
+    assert!(!flags.contains_matching(&Pattern::glob("do?g")));
+    assert_eq!(flags.filter_matching(&Pattern::glob("do*")), Animal::Dog | Animal::Dolphin);
+    assert_eq!(flags.filter_matching(&Pattern::glob("region*")).len(), 2);
+    assert!(flags.filter_matching(&Pattern::suffix("cat")).is_empty());
+
+    assert!(flags.contains((Animal::Cat, Pattern::suffix("us"))));
+    assert!(flags.contains(Not(Pattern::prefix("cat"))));
+    assert!(flags.is_subset((Pattern::prefix("do"), Pattern::prefix("region"))));
+    assert!(!flags.is_subset(Pattern::prefix("do")));
+    assert!(flags.filter_matching(&Pattern::prefix("do")).is_subset(Pattern::prefix("do")));
+
+    let glob = Pattern::glob("a*b?c*");
+    assert!(glob.is_match("abxc"));
+    assert!(glob.is_match("aaabbbxcdd"));
+    assert!(!glob.is_match("abc"));
+    assert!(Pattern::glob("*").is_match(""));
+    assert!(!Pattern::glob("?").is_match(""));
+    assert_eq!(Pattern::prefix("region").to_string(), "region*");
+
+    assert!(flags.contains(Pattern::prefix("Do")));
+    assert!(flags.contains_matching(&Pattern::glob("Region??")));
+    assert_eq!(flags.filter_matching(&Pattern::suffix("US")), Flags::new(Animal::new("RegionUs")));
+    assert!(flags.is_subset((Pattern::prefix("DO"), Pattern::prefix("Region"))));
+    assert!(!Pattern::prefix("Do").is_match("dog"));
+
+    let snake = Pattern::prefix("Dark_");
+    assert_eq!(snake.normalized::<Snake>().into_owned(), Pattern::prefix("dark_"));
+    assert_eq!(Pattern::glob("Dark*Blue?").normalized::<Snake>().into_owned(), Pattern::glob("dark*blue?"));
+    assert_eq!(Pattern::suffix("-Blue").normalized::<Snake>().into_owned(), Pattern::suffix("_blue"));
+    assert!(matches!(Pattern::glob("dark_*").normalized::<Snake>(), std::borrow::Cow::Borrowed(_)));
+    let colors: Flags<Snake> = [Snake::DarkBlue, Snake::new("DarkRed"), Snake::Html].into_iter().collect();
+    let dark: Flags<Snake> = [Snake::DarkBlue, Snake::new("dark_red")].into_iter().collect();
+    assert_eq!(colors.filter_matching(&Pattern::prefix("Dark ")), dark);
+}
+
+#[test]
+fn expressions() {
+    use strflags::expr::{Expr, ExprErrorKind};
+
+    let expr: Expr<Animal> = "Dog & !Cat | whale".parse().unwrap();
+    assert_eq!(expr, Expr::Or(vec![
+        Expr::And(vec![
+            Expr::Item(Animal::Dog),
+            Expr::Not(Box::new(Expr::Item(Animal::Cat))),
+        ]),
+        Expr::Item(Animal::Whale),
+    ]));
+    assert!(expr.eval(&Flags::<Animal>::new(Animal::Dog)));
+    assert!(expr.eval(&(Animal::Cat | Animal::Whale)));
+    assert!(!expr.eval(&(Animal::Dog | Animal::Cat)));
+    assert!(!expr.eval(&Flags::<Animal>::EMPTY));
+    assert!(expr.eval(&LargeFlags::<Animal>::new(Animal::Whale)));
+    assert!((Animal::Dog | Animal::Rabbit).contains(&expr));
+    assert!(!(Animal::Dog | Animal::Cat).contains(All(&expr)));
+    assert_eq!(expr.items().collect::<Vec<_>>(), [&Animal::Dog, &Animal::Cat, &Animal::Whale]);
+
+    let expr: Expr<Animal> = "!(GreatWhite | dog) & (cat)".parse().unwrap();
+    assert!(expr.eval(&Flags::<Animal>::new(Animal::Cat)));
+    assert!(!expr.eval(&(Animal::Cat | Animal::new("GreatWhite"))));
+    assert_eq!(expr.to_string(), "!(greatwhite | dog) & cat");
+    for s in ["a | b & c", "(a | b) & c", "!!a", "a & (b & c)", "(a & b) & c", "!(a & b)"] {
+        let expr: Expr<Animal> = s.parse().unwrap();
+        assert_eq!(expr.to_string().parse::<Expr<Animal>>().unwrap(), expr, "{}", s);
+    }
+    assert_eq!("a & b & c | d".parse::<Expr<Animal>>().unwrap().to_string(), "a & b & c | d");
+    assert_eq!("(a & b) & c".parse::<Expr<Animal>>().unwrap().to_string(), "(a & b) & c");
+    assert_eq!("a & (b & c)".parse::<Expr<Animal>>().unwrap().to_string(), "a & (b & c)");
+
+    let err = "dog & ".parse::<Expr<Animal>>().unwrap_err();
+    assert_eq!(err.kind(), &ExprErrorKind::UnexpectedEnd);
+    assert_eq!(err.span(), 6..6);
+    let err = "dog & | cat".parse::<Expr<Animal>>().unwrap_err();
+    assert_eq!(err.kind(), &ExprErrorKind::Unexpected('|'));
+    assert_eq!(err.span(), 6..7);
+    let err = "dog & (cat".parse::<Expr<Animal>>().unwrap_err();
+    assert_eq!(err.kind(), &ExprErrorKind::Unclosed);
+    assert_eq!(err.span(), 6..7);
+    assert_eq!(err.to_string(), "unclosed `(` at 6..7");
+    let err = "dog) ".parse::<Expr<Animal>>().unwrap_err();
+    assert_eq!(err.kind(), &ExprErrorKind::Unexpected(')'));
+    assert!("".parse::<Expr<Animal>>().is_err());
+
+    let err = "get | (post &  put)".parse::<Expr<Method>>().unwrap_err();
+    assert!(matches!(err.kind(), ExprErrorKind::Item(_)));
+    assert_eq!(err.span(), 15..18);
+
+    let err = "(dog) cat".parse::<Expr<Animal>>().unwrap_err();
+    assert_eq!(err.kind(), &ExprErrorKind::UnexpectedItem);
+    assert_eq!(err.span(), 6..9);
+    assert_eq!(err.to_string(), "unexpected item at 6..9");
+    let err = "dog cat".parse::<Expr<Animal>>().unwrap_err();
+    assert_eq!(err.kind(), &ExprErrorKind::UnexpectedItem);
+    assert_eq!(err.span(), 4..7);
+    let err = "!(dog \t great  white)".parse::<Expr<Animal>>().unwrap_err();
+    assert_eq!(err.kind(), &ExprErrorKind::UnexpectedItem);
+    assert_eq!(err.span(), 8..13);
+}
+
+#[test]
+fn expression_depth() {
+    use strflags::expr::{Expr, ExprErrorKind, MAX_DEPTH};
+    let nested = |depth: usize, op: &str| format!("{}dog{}", op.repeat(depth), if op == "(" { ")".repeat(depth) } else { String::new() });
+
+    let deepest = nested(MAX_DEPTH, "!").parse::<Expr<Animal>>().unwrap();
+    assert!(deepest.eval(&Flags::<Animal>::new(Animal::Dog)));
+    assert_eq!(deepest.to_string(), nested(MAX_DEPTH, "!"));
+    let err = nested(MAX_DEPTH + 1, "!").parse::<Expr<Animal>>().unwrap_err();
+    assert_eq!(err.kind(), &ExprErrorKind::TooDeep);
+    assert_eq!(err.span(), MAX_DEPTH..MAX_DEPTH + 1);
+    let err = nested(200_000, "!").parse::<Expr<Animal>>().unwrap_err();
+    assert_eq!(err.kind(), &ExprErrorKind::TooDeep);
+
+    assert!(nested(MAX_DEPTH, "(").parse::<Expr<Animal>>().is_ok());
+    let err = nested(200_000, "(").parse::<Expr<Animal>>().unwrap_err();
+    assert_eq!(err.kind(), &ExprErrorKind::TooDeep);
+
+    let chain = |len: usize, op: &str| vec!["dog"; len].join(op);
+    let expr = chain(200_000, " | ").parse::<Expr<Animal>>().unwrap();
+    assert!(matches!(&expr, Expr::Or(items) if items.len() == 200_000));
+    assert!(expr.eval(&Flags::<Animal>::new(Animal::Dog)));
+    assert_eq!(expr.to_string().len(), chain(200_000, " | ").len());
+    let expr = format!("({}) & cat", chain(200_000, " & ")).parse::<Expr<Animal>>().unwrap();
+    assert!(!expr.eval(&Flags::<Animal>::new(Animal::Dog)));
+    assert!(format!("{}dog & cat", "!".repeat(MAX_DEPTH)).parse::<Expr<Animal>>().is_ok());
+    let err = format!("dog | {}(cat & dog", "!(".repeat(MAX_DEPTH / 2)).parse::<Expr<Animal>>().unwrap_err();
+    assert_eq!(err.kind(), &ExprErrorKind::TooDeep);
+    assert_eq!(err.span(), 6 + MAX_DEPTH..7 + MAX_DEPTH);
+}
+
+str_flags! {
+    Permission: [
+        Admin: [Write, Delete, Audit],
+        /// Editors
+        Write = "edit" ["modify"]: [Read],
+        Read,
+        Delete: [],
+        Audit: [Read],
+    ]
+}
+
+#[test]
+fn hierarchy() {
+    assert!(Animal::IMPLIES.is_empty());
+    assert_eq!(Permission::IMPLIES, &[0b11110, 0b100, 0, 0, 0b100]);
+
+    let admin = Flags::new(Permission::Admin);
+    assert!(admin.contains(Permission::Read));
+    assert!(admin.contains("modify"));
+    assert!(admin.contains_all(Permission::Write | Permission::Delete | Permission::Read));
+    assert!(admin.contains(Pattern::prefix("ed")));
+    assert!(admin.contains_matching(&Pattern::prefix("ed")));
+    assert_eq!(admin.filter_matching(&Pattern::glob("*e*")), Permission::Write | Permission::Delete | Permission::Read);
+    assert!(!LargeFlags::from(admin.clone()).contains(Permission::Read));
+    assert!(LargeFlags::from(admin.expand()).contains(Permission::Read));
+    assert!(!admin.is_disjoint(Permission::Audit));
+    assert!("read & delete".parse::<strflags::expr::Expr<Permission>>().unwrap().eval(&admin));
+    assert_eq!(admin.len(), 1);
+    assert_eq!(admin.implied_bits(), 0b11111);
+    assert_eq!(admin.expand().len(), 5);
+    assert_eq!(admin.expand().compact(), admin);
+
+    let write = Permission::Write | Permission::new("Deploy");
+    assert!(write.contains(Permission::Read));
+    assert!(!write.contains(Permission::Delete));
+    assert_eq!(write.expand(), Permission::Write | Permission::Read | Permission::new("Deploy"));
+    assert_eq!((Permission::Audit | Permission::Read | Permission::Delete).compact(), Permission::Audit | Permission::Delete);
+    assert_eq!((Permission::Admin | Permission::Read).compact(), admin);
+    assert_ne!(admin, Permission::Admin | Permission::Read);
+}
+
+#[test]
+fn large() {
+    type L = LargeFlags<Animal>;
+    let mut tags: L = (0..500).map(|i| Animal::new(&format!("tag{}", i))).collect();
+    assert_eq!(tags.len(), 500);
+    assert!(tags.contains("tag42"));
+    assert!(!tags.contains(Animal::Dog));
+
+    tags |= Animal::Dog;
+    tags += Animal::Dog;
+    assert_eq!(tags.len(), 501);
+    assert!(tags.contains(&[Animal::Cat, Animal::Dog][..]));
+    tags -= Animal::new("tag0");
+    tags ^= Animal::new("tag1");
+    tags ^= Animal::Cat;
+    assert_eq!(tags.len(), 500);
+    assert!(!tags.contains("tag1"));
+    assert!(tags.contains(Animal::Cat));
+
+    let small = L::pair(Animal::Cat, Animal::Whale);
+    assert!(tags.contains(&small));
+    assert_eq!((tags.clone() & small.clone()).len(), 1);
+    assert_eq!((tags.clone() | small.clone()).len(), 501);
+    assert_eq!((tags.clone() - small.clone()).len(), 499);
+    assert_eq!((tags.clone() ^ small.clone()).len(), 500);
+    assert_eq!(tags.clone() & Animal::Whale, L::empty());
+
+    let pets = L::from(Animal::Dog | Animal::new("Shark") | Animal::Cat);
+    assert_eq!(pets.to_string(), "dog|cat|shark");
+    assert_eq!("shark|cat|dog".parse::<L>().unwrap(), pets);
+    assert_eq!(Flags::from(pets.clone()), Animal::Dog | Animal::Cat | Animal::new("Shark"));
+    assert_eq!(L::all().len(), Animal::VARIANTS.len());
+
+    assert!(tags.contains_all(small.clone() - Animal::Whale));
+    assert!(!tags.contains_all(&small));
+    assert!(tags.contains_none(Animal::Whale));
+    assert!(tags.is_superset(&["tag2", "dog"][..]));
+    assert!(pets.is_subset(tags.clone() + Animal::new("Shark")));
+    assert!(!pets.is_subset(&tags));
+    assert!(pets.is_disjoint(Animal::Whale));
+
+    let text = (0..20000).map(|i| format!("tag{}", i)).collect::<Vec<_>>().join("|");
+    let parsed: L = text.parse().unwrap();
+    assert_eq!(parsed.len(), 20000);
+    let reversed: L = (0..20000).rev().map(|i| Animal::new(&format!("tag{}", i))).collect();
+    let set: std::collections::HashSet<L> = [parsed, reversed].into_iter().collect();
+    assert_eq!(set.len(), 1);
+}
+
 str_flags! {
     #[derive(Default, PartialOrd, Ord)]
     pub Language: [
@@ -154,6 +816,24 @@ str_flags! {
     ]
 }
 
+str_flags! {
+    Dialect: [
+        Rust,
+        CPlusPlus = ["c++", "cpp", "CXX"]
+    ]
+}
+
+#[test]
+fn aliases() {
+    assert_eq!(Dialect::new("c++"), Dialect::CPlusPlus);
+    assert_eq!(Dialect::new("Cpp"), Dialect::CPlusPlus);
+    assert_eq!(Dialect::new("cxx"), Dialect::CPlusPlus);
+    assert!(Dialect::new("cxx").is_known());
+    assert_eq!(Dialect::try_new("c++"), Ok(Dialect::CPlusPlus));
+    assert_eq!(Dialect::new("c++").to_string(), "cplusplus");
+    assert_eq!(Dialect::NAMES, &["rust", "cplusplus"]);
+}
+
 #[test]
 fn test_v3() {
     assert!(Language::default() == "");